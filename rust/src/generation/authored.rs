@@ -0,0 +1,140 @@
+use crate::generation::FloorGenerator;
+use crate::generation::job::JobProgress;
+use crate::types::floor_layout::FloorLayout;
+use crate::types::rooms::RoomType;
+use crate::types::seed::SeedTree;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FloorFileError {
+    #[error("invalid floor file: {0}")]
+    InvalidFormat(#[from] serde_json::Error),
+
+    #[error("room {0:?} is listed more than once")]
+    DuplicateRoom((i64, i64)),
+
+    #[error("there is no starting room at (0, 0)")]
+    MissingStartRoom,
+
+    #[error("corridor from {0:?} to {1:?} does not join two adjacent rooms")]
+    InvalidCorridor((i64, i64), (i64, i64)),
+
+    #[error("teleporter at {0:?} is not paired with a teleporter leading back to it")]
+    UnpairedTeleporter((i64, i64)),
+
+    #[error("teleporter at {0:?} leads back to itself")]
+    SelfTeleporter((i64, i64)),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FloorFile {
+    #[serde(default)]
+    pub par_time: Option<f64>,
+
+    pub rooms: Vec<FloorFileRoom>,
+
+    // every pair of adjacent rooms is joined when left out
+    #[serde(default)]
+    pub corridors: Option<Vec<FloorFileCorridor>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FloorFileRoom {
+    pub position: (i64, i64),
+
+    #[serde(default = "FloorFileRoom::default_room_type", rename = "type")]
+    pub room_type: RoomType,
+}
+
+impl FloorFileRoom {
+    fn default_room_type() -> RoomType {
+        RoomType::Normal
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FloorFileCorridor {
+    pub from: (i64, i64),
+    pub to: (i64, i64),
+
+    #[serde(default)]
+    pub one_way: bool,
+}
+
+pub struct AuthoredGenerator {
+    layout: FloorLayout,
+    par_time: Option<f64>,
+}
+
+impl AuthoredGenerator {
+    pub fn parse(text: &str) -> Result<AuthoredGenerator, FloorFileError> {
+        let floor_file = serde_json::from_str::<FloorFile>(text)?;
+
+        let mut layout = FloorLayout::new();
+
+        for room in floor_file.rooms.iter() {
+            if layout.contains_room(&room.position) {
+                return Err(FloorFileError::DuplicateRoom(room.position));
+            }
+
+            layout.add_room(room.position, room.room_type.clone());
+        }
+
+        if !layout.contains_room(&(0, 0)) {
+            return Err(FloorFileError::MissingStartRoom);
+        }
+
+        match floor_file.corridors {
+            Some(ref corridors) => {
+                for corridor in corridors {
+                    let is_adjacent = (corridor.from.0 - corridor.to.0).abs()
+                        + (corridor.from.1 - corridor.to.1).abs()
+                        == 1;
+
+                    if !is_adjacent
+                        || !layout.contains_room(&corridor.from)
+                        || !layout.contains_room(&corridor.to)
+                    {
+                        return Err(FloorFileError::InvalidCorridor(corridor.from, corridor.to));
+                    }
+
+                    if corridor.one_way {
+                        layout.connect_one_way(corridor.from, corridor.to);
+                    } else {
+                        layout.connect(corridor.from, corridor.to);
+                    }
+                }
+            }
+            None => layout.connect_adjacent(),
+        }
+
+        for room in floor_file.rooms.iter() {
+            if room.room_type.get_teleport_destination() == Some(room.position) {
+                return Err(FloorFileError::SelfTeleporter(room.position));
+            }
+
+            if let Some(destination) = room.room_type.get_teleport_destination()
+                && layout.get_teleport_destination(&destination) != Some(room.position)
+            {
+                return Err(FloorFileError::UnpairedTeleporter(room.position));
+            }
+        }
+
+        Ok(AuthoredGenerator {
+            layout,
+            par_time: floor_file.par_time,
+        })
+    }
+}
+
+impl FloorGenerator for AuthoredGenerator {
+    fn generate(
+        &self,
+        _seed: SeedTree,
+        _floor: i64,
+        _progress: &JobProgress,
+    ) -> (FloorLayout, Option<f64>) {
+        (self.layout.clone(), self.par_time)
+    }
+}
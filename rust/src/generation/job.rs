@@ -0,0 +1,110 @@
+use std::any::Any;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread::JoinHandle;
+
+#[derive(Clone, Debug)]
+pub struct JobProgress {
+    // f64 bits, there is no atomic float
+    progress: Arc<AtomicU64>,
+    cancelled: Arc<AtomicBool>,
+
+    start: f64,
+    end: f64,
+}
+
+impl Default for JobProgress {
+    fn default() -> Self {
+        Self {
+            progress: Arc::new(AtomicU64::new(0.0f64.to_bits())),
+            cancelled: Arc::new(AtomicBool::new(false)),
+
+            start: 0.0,
+            end: 1.0,
+        }
+    }
+}
+
+impl JobProgress {
+    pub fn new() -> JobProgress {
+        JobProgress::default()
+    }
+
+    // a view that maps its own 0..1 onto part of this one, for jobs with several stages
+    pub fn scoped(&self, start: f64, end: f64) -> JobProgress {
+        let span = self.end - self.start;
+
+        JobProgress {
+            progress: self.progress.clone(),
+            cancelled: self.cancelled.clone(),
+
+            start: self.start + span * start,
+            end: self.start + span * end,
+        }
+    }
+
+    pub fn set_progress(&self, fraction: f64) {
+        let progress = self.start + (self.end - self.start) * fraction.clamp(0.0, 1.0);
+
+        self.progress.store(progress.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn get_progress(&self) -> f64 {
+        f64::from_bits(self.progress.load(Ordering::Relaxed))
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+pub struct GenerationJob<T> {
+    handle: JoinHandle<T>,
+    progress: JobProgress,
+}
+
+impl<T: Send + 'static> GenerationJob<T> {
+    pub fn spawn<F>(job: F) -> GenerationJob<T>
+    where
+        F: FnOnce(JobProgress) -> T + Send + 'static,
+    {
+        let progress = JobProgress::new();
+        let job_progress = progress.clone();
+
+        GenerationJob {
+            handle: std::thread::spawn(move || job(job_progress)),
+            progress,
+        }
+    }
+
+    pub fn get_progress(&self) -> f64 {
+        self.progress.get_progress()
+    }
+
+    // the thread winds down at its next check, its result is never looked at
+    pub fn cancel(self) {
+        self.progress.cancel();
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    pub fn join(self) -> std::thread::Result<T> {
+        self.handle.join()
+    }
+}
+
+pub fn get_panic_message(panic: &(dyn Any + Send)) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
@@ -0,0 +1,36 @@
+pub mod authored;
+pub mod job;
+pub mod par_time;
+pub mod random_walk;
+pub mod tutorial;
+
+use crate::generation::job::JobProgress;
+use crate::generation::random_walk::RandomWalkGenerator;
+use crate::generation::tutorial::{FallbackGenerator, TutorialGenerator};
+use crate::types::floor_layout::FloorLayout;
+use crate::types::seed::SeedTree;
+
+// must stay engine-free so layouts can be generated and inspected without godot
+pub trait FloorGenerator: Send {
+    // a generator only returns a par time when it was authored by hand,
+    // otherwise it is solved from the layout and the player's properties.
+    // long running generators should report progress and stop early once cancelled
+    fn generate(
+        &self,
+        seed: SeedTree,
+        floor: i64,
+        progress: &JobProgress,
+    ) -> (FloorLayout, Option<f64>);
+}
+
+pub fn generator_for_floor(floor: i64) -> Box<dyn FloorGenerator> {
+    match floor {
+        0 => Box::new(TutorialGenerator),
+        _ => Box::new(RandomWalkGenerator::default()),
+    }
+}
+
+// used when everything else failed, small and fixed so it can't go wrong
+pub fn fallback_generator() -> Box<dyn FloorGenerator> {
+    Box::new(FallbackGenerator)
+}
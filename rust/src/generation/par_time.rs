@@ -0,0 +1,435 @@
+use crate::generation::job::JobProgress;
+use crate::types::floor_layout::{FloorLayout, ROOM_GRID_BASIS};
+use crate::types::movement::MovementModel;
+use crate::types::player_properties::PlayerProperties;
+use crate::types::room_sections::{get_sections_seen_from, merge_sections};
+use crate::types::simulation::SIMULATION_TICK_RATE;
+use std::collections::{HashMap, VecDeque};
+
+pub struct ParTimeSolver {
+    pub tick_rate: f64,
+    pub slack: f64,
+
+    // share of the floor the tour has to see, whole rooms are counted rather than sections
+    // so it is never shorter than the run the player needs
+    pub completion_threshold: f64,
+}
+
+impl Default for ParTimeSolver {
+    fn default() -> Self {
+        Self {
+            tick_rate: SIMULATION_TICK_RATE as f64,
+            slack: 1.5,
+
+            completion_threshold: 1.0,
+        }
+    }
+}
+
+impl ParTimeSolver {
+    pub fn solve(
+        &self,
+        layout: &FloorLayout,
+        properties: &PlayerProperties,
+        progress: &JobProgress,
+    ) -> f64 {
+        let route = self.plan_route(layout, properties, progress);
+
+        self.simulate_route(layout, &route, properties) * self.slack
+    }
+
+    // greedy tour: keep walking to the nearest room that would reveal something new
+    pub fn plan_route(
+        &self,
+        layout: &FloorLayout,
+        properties: &PlayerProperties,
+        progress: &JobProgress,
+    ) -> Vec<(i64, i64)> {
+        let mut route = vec![(0, 0)];
+
+        if !layout.contains_room(&(0, 0)) {
+            return route;
+        }
+
+        // rooms only count as seen once every section of them has been, same as the fog
+        let mut seen_sections = HashMap::new();
+
+        Self::look_from(layout, (0, 0), properties, &mut seen_sections);
+
+        let room_count = layout.room_count() as f64;
+
+        loop {
+            let seen_room_count = Self::get_seen_room_count(&seen_sections);
+
+            if seen_room_count as f64 >= room_count * self.completion_threshold
+                || progress.is_cancelled()
+            {
+                break;
+            }
+
+            progress.set_progress(seen_room_count as f64 / room_count);
+
+            let current = *route.last().unwrap();
+
+            let Some(path) = Self::find_path_to_reveal(layout, current, &seen_sections, properties)
+            else {
+                // the rest of the floor is unreachable, nothing more to plan
+                break;
+            };
+
+            let path_start = route.len();
+
+            route.extend(path);
+
+            let teleport_steps = Self::get_teleport_steps(layout, &route);
+
+            for (i, position) in route.iter().enumerate().skip(path_start) {
+                let is_teleporting = teleport_steps.get(i).copied().unwrap_or(false);
+
+                // the player never stands in a teleporter they enter, only the room itself is revealed
+                if is_teleporting {
+                    seen_sections.insert(*position, [true; 7]);
+                } else {
+                    Self::look_from(layout, *position, properties, &mut seen_sections);
+                }
+            }
+        }
+
+        progress.set_progress(1.0);
+
+        route
+    }
+
+    // mirrors MovementModel::tick: every change of direction is a full stop
+    // followed by a stop cooldown, and straight runs keep their momentum; the
+    // runs and cooldowns are exact, only the switch back to moving costs a tick
+    pub fn simulate_route(
+        &self,
+        layout: &FloorLayout,
+        route: &[(i64, i64)],
+        properties: &PlayerProperties,
+    ) -> f64 {
+        let delta = 1.0 / self.tick_rate;
+
+        let mut runs: Vec<(_, i64, _)> = vec![];
+
+        let teleport_steps = Self::get_teleport_steps(layout, route);
+
+        for (step, is_teleport) in route.windows(2).zip(teleport_steps) {
+            // teleporting keeps speed and direction, so the run carries on from the partner
+            if is_teleport {
+                continue;
+            }
+
+            let direction = (step[1].0 - step[0].0, step[1].1 - step[0].1);
+
+            match runs.last_mut() {
+                Some((run_direction, length, end)) if *run_direction == direction => {
+                    *length += 1;
+                    *end = step[1];
+                }
+                _ => runs.push((direction, 1, step[1])),
+            }
+        }
+
+        let mut time = 0.0;
+
+        for (i, (_, length, end)) in runs.iter().enumerate() {
+            // the tick spent switching into Moving
+            time += delta;
+
+            let distance = *length as f64 * ROOM_GRID_BASIS as f64;
+
+            let run_time = MovementModel::get_travel_time(properties, 0.0, distance);
+
+            time += run_time;
+
+            if i + 1 < runs.len() {
+                let speed = (properties.active_acceleration * run_time).min(properties.max_speed);
+
+                time += properties.get_stop_cooldown(speed) + layout.get_stop_penalty(end);
+            }
+        }
+
+        time
+    }
+
+    // whether each step of a route goes through a teleporter rather than down a corridor; a
+    // teleporter walked into always sends the player on, even to a partner right next door,
+    // while one the player landed in or started from is left on foot like any other room
+    fn get_teleport_steps(layout: &FloorLayout, route: &[(i64, i64)]) -> Vec<bool> {
+        let mut teleport_steps = vec![];
+        let mut walked_in = false;
+
+        for step in route.windows(2) {
+            let is_teleport =
+                walked_in && layout.get_teleport_destination(&step[0]) == Some(step[1]);
+
+            teleport_steps.push(is_teleport);
+
+            walked_in = !is_teleport;
+        }
+
+        teleport_steps
+    }
+
+    // the sections of every room in line of sight the player would uncover standing in the
+    // middle of a room, from the same model the floor reveals fog with
+    fn get_sections_seen_from(
+        layout: &FloorLayout,
+        position: (i64, i64),
+        properties: &PlayerProperties,
+    ) -> Vec<((i64, i64), [bool; 7])> {
+        let grid_basis = ROOM_GRID_BASIS as f64;
+
+        let viewer_position = (
+            position.0 as f64 * grid_basis,
+            position.1 as f64 * grid_basis,
+        );
+
+        let view_range = properties.view_distance + (grid_basis / 2.0);
+
+        layout
+            .get_visible_rooms(viewer_position, view_range)
+            .into_iter()
+            .map(|room| {
+                let room_position = (room.0 as f64 * grid_basis, room.1 as f64 * grid_basis);
+
+                (room, get_sections_seen_from(room_position, viewer_position))
+            })
+            .collect()
+    }
+
+    fn look_from(
+        layout: &FloorLayout,
+        position: (i64, i64),
+        properties: &PlayerProperties,
+        seen_sections: &mut HashMap<(i64, i64), [bool; 7]>,
+    ) {
+        for (room, sections) in Self::get_sections_seen_from(layout, position, properties) {
+            let room_sections = seen_sections.entry(room).or_insert([false; 7]);
+
+            *room_sections = merge_sections(*room_sections, sections);
+        }
+    }
+
+    fn is_room_seen(seen_sections: &HashMap<(i64, i64), [bool; 7]>, room: &(i64, i64)) -> bool {
+        seen_sections
+            .get(room)
+            .is_some_and(|sections| sections.iter().all(|seen| *seen))
+    }
+
+    fn get_seen_room_count(seen_sections: &HashMap<(i64, i64), [bool; 7]>) -> usize {
+        seen_sections
+            .keys()
+            .filter(|room| Self::is_room_seen(seen_sections, room))
+            .count()
+    }
+
+    fn reveals_anything(
+        layout: &FloorLayout,
+        position: (i64, i64),
+        seen_sections: &HashMap<(i64, i64), [bool; 7]>,
+        properties: &PlayerProperties,
+    ) -> bool {
+        Self::get_sections_seen_from(layout, position, properties)
+            .iter()
+            .any(|(room, sections)| {
+                let room_sections = seen_sections.get(room).copied().unwrap_or([false; 7]);
+
+                merge_sections(room_sections, *sections) != room_sections
+            })
+    }
+
+    fn find_path_to_reveal(
+        layout: &FloorLayout,
+        start: (i64, i64),
+        seen_sections: &HashMap<(i64, i64), [bool; 7]>,
+        properties: &PlayerProperties,
+    ) -> Option<Vec<(i64, i64)>> {
+        // where each room was reached from, and the rooms passed on the way without stopping
+        let mut previous: HashMap<_, (_, Vec<_>)> = HashMap::new();
+        let mut queue = VecDeque::from([start]);
+
+        while let Some(position) = queue.pop_front() {
+            let reveals_on_the_way = match previous.get(&position) {
+                Some((before, passed)) => Self::reveals_on_the_way(
+                    layout,
+                    *before,
+                    passed,
+                    position,
+                    seen_sections,
+                    properties,
+                ),
+                None => false,
+            };
+
+            if position != start
+                && (reveals_on_the_way
+                    || Self::reveals_anything(layout, position, seen_sections, properties))
+            {
+                let mut path = vec![];
+                let mut current = position;
+
+                while let Some((before, passed)) = previous.get(&current) {
+                    path.push(current);
+                    path.extend(passed.iter().rev());
+
+                    current = *before;
+                }
+
+                path.reverse();
+
+                return Some(path);
+            }
+
+            for next in layout.get_exits(position) {
+                let direction = (next.0 - position.0, next.1 - position.1);
+
+                let Some(mut passed) = Self::get_forced_path(layout, next, direction) else {
+                    continue;
+                };
+
+                let landing = passed.pop().unwrap();
+
+                if landing != start && !previous.contains_key(&landing) {
+                    previous.insert(landing, (position, passed));
+                    queue.push_back(landing);
+                }
+            }
+        }
+
+        None
+    }
+
+    // every room the player goes through once they head into `next`, mirroring
+    // MovementModel::arrive: teleporters send them on to their partner and ice keeps them
+    // going the same way, the last room is the first one they can stop or turn in
+    fn get_forced_path(
+        layout: &FloorLayout,
+        next: (i64, i64),
+        direction: (i64, i64),
+    ) -> Option<Vec<(i64, i64)>> {
+        let mut path = vec![];
+        let mut position = next;
+
+        loop {
+            path.push(position);
+
+            if let Some(destination) = layout.get_teleport_destination(&position) {
+                position = destination;
+
+                path.push(position);
+            }
+
+            let slide_position = (position.0 + direction.0, position.1 + direction.1);
+
+            let is_forced_to_move = layout
+                .get_room(&position)
+                .is_some_and(|room_type| room_type.forces_movement())
+                && layout.can_move(position, slide_position);
+
+            if !is_forced_to_move {
+                return Some(path);
+            }
+
+            // teleporters can send a slide round in circles the player never gets out of
+            if path.len() > layout.room_count() * 2 {
+                return None;
+            }
+
+            position = slide_position;
+        }
+    }
+
+    // the rooms slid or teleported through show things too, a teleporter walked into is
+    // revealed whole
+    fn reveals_on_the_way(
+        layout: &FloorLayout,
+        before: (i64, i64),
+        passed: &[(i64, i64)],
+        landing: (i64, i64),
+        seen_sections: &HashMap<(i64, i64), [bool; 7]>,
+        properties: &PlayerProperties,
+    ) -> bool {
+        let mut steps = vec![before];
+
+        steps.extend(passed);
+        steps.push(landing);
+
+        let teleport_steps = Self::get_teleport_steps(layout, &steps);
+
+        passed.iter().enumerate().any(|(i, room)| {
+            if teleport_steps.get(i + 1).copied().unwrap_or(false) {
+                !Self::is_room_seen(seen_sections, room)
+            } else {
+                Self::reveals_anything(layout, *room, seen_sections, properties)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::rooms::{HazardType, RoomType};
+
+    #[test]
+    fn adjacent_teleporter_pair_is_not_walked() {
+        let mut layout = FloorLayout::new();
+
+        layout.add_room((0, 0), RoomType::Normal);
+        layout.add_room((1, 0), RoomType::Teleporter((1, 1)));
+        layout.add_room((1, 1), RoomType::Teleporter((1, 0)));
+        layout.add_room((2, 1), RoomType::Normal);
+
+        layout.connect_adjacent();
+
+        let properties = PlayerProperties::default();
+        let solver = ParTimeSolver::default();
+
+        // right into the entrance, out of its partner still going right
+        let time = solver.simulate_route(&layout, &[(0, 0), (1, 0), (1, 1), (2, 1)], &properties);
+
+        let straight_run = 1.0 / solver.tick_rate
+            + MovementModel::get_travel_time(&properties, 0.0, 2.0 * ROOM_GRID_BASIS as f64);
+
+        assert!(
+            (time - straight_run).abs() < 1e-9,
+            "{time} != {straight_run}"
+        );
+    }
+
+    #[test]
+    fn ice_is_slid_through_not_turned_in() {
+        let mut layout = FloorLayout::new();
+
+        // ice in the middle of the bottom row, the room above it is only reachable the long way
+        layout.add_room((0, 0), RoomType::Normal);
+        layout.add_room((1, 0), RoomType::Hazard(HazardType::Ice));
+        layout.add_room((2, 0), RoomType::Normal);
+        layout.add_room((2, 1), RoomType::Normal);
+        layout.add_room((1, 1), RoomType::Normal);
+
+        layout.connect_adjacent();
+
+        let route = ParTimeSolver::default().plan_route(
+            &layout,
+            &PlayerProperties::default(),
+            &JobProgress::new(),
+        );
+
+        assert!(route.contains(&(1, 1)), "{route:?}");
+
+        for step in route.windows(3) {
+            if step[1] != (1, 0) {
+                continue;
+            }
+
+            let direction_in = (step[1].0 - step[0].0, step[1].1 - step[0].1);
+            let direction_out = (step[2].0 - step[1].0, step[2].1 - step[1].1);
+
+            assert_eq!(direction_in, direction_out, "{route:?}");
+        }
+    }
+}
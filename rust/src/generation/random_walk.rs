@@ -0,0 +1,171 @@
+use crate::generation::FloorGenerator;
+use crate::generation::job::JobProgress;
+use crate::types::floor_layout::FloorLayout;
+use crate::types::rooms::{HazardType, RoomType};
+use crate::types::seed::{SeedStream, SeedTree};
+use rand::prelude::*;
+
+pub struct RandomWalkGenerator {
+    pub base_room_count: i64,
+    pub room_count_variance: i64,
+
+    pub treasure_chance: f64,
+
+    pub hazard_chance_per_floor: f64,
+    pub max_hazard_chance: f64,
+
+    pub rooms_per_teleporter_pair: usize,
+    pub min_teleporter_shortcut: i64,
+}
+
+impl Default for RandomWalkGenerator {
+    fn default() -> Self {
+        Self {
+            base_room_count: 20,
+            room_count_variance: 10,
+
+            treasure_chance: 0.08,
+
+            hazard_chance_per_floor: 0.02,
+            max_hazard_chance: 0.25,
+
+            rooms_per_teleporter_pair: 40,
+            min_teleporter_shortcut: 8,
+        }
+    }
+}
+
+impl RandomWalkGenerator {
+    pub fn get_hazard_chance(&self, floor: i64) -> f64 {
+        (self.hazard_chance_per_floor * floor.max(0) as f64).min(self.max_hazard_chance)
+    }
+
+    // links a random room to the room furthest from it on foot, so every pair
+    // is a real shortcut rather than a detour
+    fn place_teleporters(&self, rooms: &mut FloorLayout, rng: &mut impl Rng) {
+        let mut candidates: Vec<(i64, i64)> = rooms
+            .rooms()
+            .map(|(position, _)| *position)
+            .filter(|position| *position != (0, 0))
+            .collect();
+
+        // sorted so the seed alone decides the result
+        candidates.sort();
+
+        for _ in 0..(rooms.room_count() / self.rooms_per_teleporter_pair.max(1)) {
+            if candidates.len() < 2 {
+                break;
+            }
+
+            let entrance = candidates[rng.random_range(0..candidates.len())];
+
+            let distances = rooms.get_distances(entrance);
+
+            let Some((exit, distance)) = candidates
+                .iter()
+                .filter_map(|position| {
+                    distances
+                        .get(position)
+                        .map(|distance| (*position, *distance))
+                })
+                .max_by_key(|(_, distance)| *distance)
+            else {
+                continue;
+            };
+
+            if distance < self.min_teleporter_shortcut {
+                continue;
+            }
+
+            rooms.add_room(entrance, RoomType::Teleporter(exit));
+            rooms.add_room(exit, RoomType::Teleporter(entrance));
+
+            candidates.retain(|position| *position != entrance && *position != exit);
+        }
+    }
+}
+
+impl FloorGenerator for RandomWalkGenerator {
+    fn generate(
+        &self,
+        seed: SeedTree,
+        floor: i64,
+        progress: &JobProgress,
+    ) -> (FloorLayout, Option<f64>) {
+        let mut rng = seed.stream(SeedStream::FloorLayout).rng();
+
+        let mut rooms = FloorLayout::new();
+
+        let mut position: (i64, i64) = (0, 0);
+        let mut previous_position: Option<(i64, i64)> = None;
+
+        let max_room_count = self.base_room_count
+            + (floor.pow(2))
+            + rng.random_range(-self.room_count_variance..=self.room_count_variance);
+
+        for i in 0..max_room_count {
+            if progress.is_cancelled() {
+                return (rooms, None);
+            }
+
+            progress.set_progress(i as f64 / max_room_count as f64);
+
+            rooms.add_room(position, RoomType::Normal);
+
+            // only the steps the walk actually took become corridors
+            if let Some(previous_position) = previous_position {
+                rooms.connect(previous_position, position);
+            }
+
+            previous_position = Some(position);
+
+            let mut delta = (rng.random_range(-1..=1), rng.random_range(-1..=1));
+
+            if delta.0 != 0 && delta.1 != 0 {
+                if rng.random_bool(0.5) {
+                    delta.0 = 0;
+                } else {
+                    delta.1 = 0;
+                }
+            }
+
+            position = (position.0 + delta.0, position.1 + delta.1);
+        }
+
+        self.place_teleporters(&mut rooms, &mut rng);
+
+        let content_seed = seed.stream(SeedStream::RoomContent);
+
+        let positions: Vec<(i64, i64)> = rooms.rooms().map(|(position, _)| *position).collect();
+
+        for position in positions {
+            if position == (0, 0) || rooms.get_room(&position) != Some(&RoomType::Normal) {
+                continue;
+            }
+
+            // seeded per room so placement doesn't depend on iteration order
+            let mut room_rng = content_seed.position(position).rng();
+
+            if room_rng.random_bool(self.treasure_chance) {
+                rooms.add_room(
+                    position,
+                    RoomType::Treasure(room_rng.random_range(1..=3) + floor.max(0) / 5),
+                );
+            } else if room_rng.random_bool(self.get_hazard_chance(floor)) {
+                // ice at a junction would make some exits impossible to take
+                let hazard = if rooms.get_exits(position).count() <= 2 && room_rng.random_bool(0.5)
+                {
+                    HazardType::Ice
+                } else {
+                    HazardType::Spikes
+                };
+
+                rooms.add_room(position, RoomType::Hazard(hazard));
+            }
+        }
+
+        progress.set_progress(1.0);
+
+        (rooms, None)
+    }
+}
@@ -0,0 +1,52 @@
+use crate::generation::FloorGenerator;
+use crate::generation::job::JobProgress;
+use crate::types::floor_layout::FloorLayout;
+use crate::types::rooms::RoomType;
+use crate::types::seed::SeedTree;
+
+pub struct TutorialGenerator;
+
+impl FloorGenerator for TutorialGenerator {
+    fn generate(
+        &self,
+        _seed: SeedTree,
+        _floor: i64,
+        _progress: &JobProgress,
+    ) -> (FloorLayout, Option<f64>) {
+        let mut layout = FloorLayout::new();
+
+        for position in [
+            (-1, 1),
+            (0, 1),
+            (0, 0),
+            (1, 0),
+            (2, 0),
+            (3, 0),
+            (4, 0),
+            (5, 0),
+            (5, -1),
+        ] {
+            layout.add_room(position, RoomType::Normal);
+        }
+
+        layout.connect_adjacent();
+
+        (layout, Some(0.0))
+    }
+}
+
+// the tutorial layout with a solved par time, for when a real floor could not be made
+pub struct FallbackGenerator;
+
+impl FloorGenerator for FallbackGenerator {
+    fn generate(
+        &self,
+        seed: SeedTree,
+        floor: i64,
+        progress: &JobProgress,
+    ) -> (FloorLayout, Option<f64>) {
+        let (layout, _) = TutorialGenerator.generate(seed, floor, progress);
+
+        (layout, None)
+    }
+}
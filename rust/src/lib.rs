@@ -1,3 +1,4 @@
+pub mod generation;
 pub mod managers;
 pub mod objects;
 pub mod types;
@@ -1,18 +1,27 @@
+use crate::generation::authored::AuthoredGenerator;
+use crate::generation::job::{GenerationJob, JobProgress, get_panic_message};
+use crate::generation::par_time::ParTimeSolver;
+use crate::generation::{FloorGenerator, fallback_generator, generator_for_floor};
 use crate::objects::map::floor::Floor;
-use crate::types::rooms::RoomType;
+use crate::types::completion_rules::CompletionRules;
+use crate::types::floor_layout::FloorLayout;
+use crate::types::player_properties::PlayerProperties;
 use crate::types::save_game::SaveGame;
-use godot::classes::{INode, Node};
+use godot::classes::{FileAccess, INode, Node};
 use godot::prelude::*;
-use rand::prelude::*;
-use std::collections::HashMap;
-use std::thread::JoinHandle;
+use std::panic::AssertUnwindSafe;
+use std::sync::Mutex;
 
-pub type FloorLayout = HashMap<(i64, i64), RoomType>;
+type FloorGenerationJob = GenerationJob<Result<(FloorLayout, f64), String>>;
+
+// outlives the shop scene so the game scene can pick up a floor started there
+// along with the completion threshold its par time was solved for
+static PREPARED_FLOOR: Mutex<Option<(SaveGame, f64, FloorGenerationJob)>> = Mutex::new(None);
 
 #[derive(GodotClass)]
 #[class(base=Node)]
 pub struct FloorManager {
-    floor_generation_thread: Option<JoinHandle<(FloorLayout, f64)>>,
+    floor_generation_job: Option<FloorGenerationJob>,
 
     #[export]
     current_floor: OnEditor<Gd<Floor>>,
@@ -20,6 +29,11 @@ pub struct FloorManager {
     pub current_floor_layout: FloorLayout,
     pub estimated_completion_time: f64,
 
+    // of the floor being set up, the rooms need them to know how far the player can see
+    player_properties: PlayerProperties,
+
+    pub completion_rules: CompletionRules,
+
     base: Base<Node>,
 }
 
@@ -29,108 +43,258 @@ impl FloorManager {
     fn level_setup_complete();
 
     #[signal]
-    fn level_setup_failed();
+    fn level_setup_failed(message: GString);
 }
 
 impl FloorManager {
-    pub fn setup_level(&mut self, save_game: SaveGame) {
-        self.floor_generation_thread = Some(std::thread::spawn(move || {
-            let mut rng = SmallRng::from_seed(save_game.get_rng_seed());
-
-            if save_game.current_floor == 0 {
-                return (
-                    HashMap::from([
-                        ((-1, 1), RoomType::Normal),
-                        ((0, 1), RoomType::Normal),
-                        ((0, 0), RoomType::Normal),
-                        ((1, 0), RoomType::Normal),
-                        ((2, 0), RoomType::Normal),
-                        ((3, 0), RoomType::Normal),
-                        ((4, 0), RoomType::Normal),
-                        ((5, 0), RoomType::Normal),
-                        ((5, -1), RoomType::Normal),
-                    ]),
-                    0.0,
-                );
+    pub fn setup_level(&mut self, save_game: SaveGame, completion_rules: CompletionRules) {
+        self.player_properties = save_game.player_properties.clone();
+
+        self.completion_rules = completion_rules;
+
+        let completion_threshold = self.completion_rules.get_threshold(save_game.current_floor);
+
+        // a restart while the last floor is still generating makes that floor stale
+        if let Some(floor_generation_job) = self.floor_generation_job.take() {
+            floor_generation_job.cancel();
+        }
+
+        let prepared_floor = match PREPARED_FLOOR.lock() {
+            Ok(mut prepared_floor) => prepared_floor.take(),
+            Err(err) => {
+                godot_error!("failed to lock prepared floor: {err}");
+
+                None
+            }
+        };
+
+        self.floor_generation_job = Some(match prepared_floor {
+            Some((prepared_game, prepared_threshold, floor_generation_job))
+                if prepared_game.generates_same_floor(&save_game)
+                    && prepared_threshold == completion_threshold =>
+            {
+                floor_generation_job
             }
+            Some((_, _, floor_generation_job)) => {
+                floor_generation_job.cancel();
+
+                Self::start_generation(save_game, completion_threshold)
+            }
+            None => Self::start_generation(save_game, completion_threshold),
+        });
+    }
 
-            let mut rooms = HashMap::new();
+    // starts generating the floor a save game is about to play, so entering it later is instant
+    pub fn prepare_level(save_game: SaveGame) {
+        let completion_threshold =
+            Self::read_completion_rules().get_threshold(save_game.current_floor);
 
-            let mut position: (i64, i64) = (0, 0);
+        let Ok(mut prepared_floor) = PREPARED_FLOOR.lock() else {
+            godot_error!("failed to lock prepared floor");
 
-            let max_room_count = 20 + (save_game.current_floor.pow(2)) + rng.random_range(-10..=10);
+            return;
+        };
 
-            // fixes rng somehow????
-            for _ in 0..3 {
-                let _ = rng.random_range(-1..=1);
+        if let Some((ref prepared_game, prepared_threshold, _)) = *prepared_floor
+            && prepared_game.generates_same_floor(&save_game)
+            && prepared_threshold == completion_threshold
+        {
+            return;
+        }
+
+        if let Some((_, _, floor_generation_job)) = prepared_floor.take() {
+            floor_generation_job.cancel();
+        }
+
+        *prepared_floor = Some((
+            save_game.clone(),
+            completion_threshold,
+            Self::start_generation(save_game, completion_threshold),
+        ));
+    }
+
+    fn start_generation(save_game: SaveGame, completion_threshold: f64) -> FloorGenerationJob {
+        let floor_file = Self::read_floor_file(save_game.current_floor);
+
+        GenerationJob::spawn(move |progress| {
+            let mut errors = vec![];
+            let mut generators: Vec<(String, Box<dyn FloorGenerator>)> = vec![];
+
+            // a broken authored floor is reported rather than quietly replaced, the fallbacks
+            // are only there for generators that crash
+            if let Some((path, text)) = floor_file {
+                match AuthoredGenerator::parse(&text) {
+                    Ok(generator) => generators.push((path, Box::new(generator))),
+                    Err(err) => return Err(format!("{path}: {err}")),
+                }
             }
 
-            for _ in 0..max_room_count {
-                rooms.insert(position, RoomType::Normal);
+            generators.push((
+                "built-in generator".to_string(),
+                generator_for_floor(save_game.current_floor),
+            ));
+            generators.push(("fallback generator".to_string(), fallback_generator()));
 
-                let mut delta = (rng.random_range(-1..=1), rng.random_range(-1..=1));
+            for (name, generator) in generators {
+                if progress.is_cancelled() {
+                    return Err("cancelled".to_string());
+                }
+
+                let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                    Self::generate_floor(
+                        generator.as_ref(),
+                        &save_game,
+                        completion_threshold,
+                        &progress,
+                    )
+                }));
 
-                if delta.0 != 0 && delta.1 != 0 {
-                    if rng.random_bool(0.5) {
-                        delta.0 = 0;
-                    } else {
-                        delta.1 = 0;
-                    }
+                match result {
+                    Ok(floor) => return Ok(floor),
+                    Err(panic) => errors.push(format!("{name}: {}", get_panic_message(&*panic))),
                 }
+            }
 
-                position = (position.0 + delta.0, position.1 + delta.1);
+            Err(errors.join("\n"))
+        })
+    }
+
+    fn generate_floor(
+        generator: &dyn FloorGenerator,
+        save_game: &SaveGame,
+        completion_threshold: f64,
+        progress: &JobProgress,
+    ) -> (FloorLayout, f64) {
+        progress.set_progress(0.0);
+
+        let (floor_layout, authored_time) = generator.generate(
+            save_game.get_floor_seed(),
+            save_game.current_floor,
+            &progress.scoped(0.0, 0.5),
+        );
+
+        let estimated_time = authored_time.unwrap_or_else(|| {
+            ParTimeSolver {
+                completion_threshold,
+                ..ParTimeSolver::default()
             }
+            .solve(
+                &floor_layout,
+                &save_game.player_properties,
+                &progress.scoped(0.5, 1.0),
+            )
+        });
+
+        (floor_layout, estimated_time)
+    }
+
+    pub fn get_generation_progress(&self) -> Option<f64> {
+        self.floor_generation_job
+            .as_ref()
+            .map(|floor_generation_job| floor_generation_job.get_progress())
+    }
+
+    fn read_floor_file(floor: i64) -> Option<(String, String)> {
+        // floors in user:// override the ones shipped with the game
+        for path in [
+            format!("user://floors/floor_{floor}.json"),
+            format!("res://floors/floor_{floor}.json"),
+        ] {
+            if FileAccess::file_exists(&path) {
+                let text = FileAccess::get_file_as_string(&path).to_string();
 
-            let estimated_time = rooms.len() as f64
-                * ((1.0 / ((save_game.current_floor.max(0) as f64 / 2.0) + 1.0)) * 2.0 + 1.0);
+                return Some((path, text));
+            }
+        }
 
-            (rooms, estimated_time)
-        }));
+        None
+    }
+
+    pub fn read_completion_rules() -> CompletionRules {
+        // rules in user:// override the ones shipped with the game
+        for path in [
+            "user://rules/completion_rules.json",
+            "res://rules/completion_rules.json",
+        ] {
+            if !FileAccess::file_exists(path) {
+                continue;
+            }
+
+            match CompletionRules::parse(&FileAccess::get_file_as_string(path).to_string()) {
+                Ok(completion_rules) => return completion_rules,
+                Err(err) => godot_error!("failed to load completion rules {path}: {err}"),
+            }
+        }
+
+        CompletionRules::default()
     }
 
     pub fn get_completion_progress(&self) -> f64 {
         self.current_floor.bind().get_completion_progress()
     }
+
+    pub fn simulate_tick(&mut self) {
+        self.current_floor.bind_mut().simulate_tick();
+    }
+
+    pub fn get_collected_coins(&self) -> i64 {
+        self.current_floor.bind().get_collected_coins()
+    }
 }
 
 #[godot_api]
 impl INode for FloorManager {
     fn init(base: Base<Node>) -> Self {
         Self {
-            floor_generation_thread: None,
+            floor_generation_job: None,
 
             current_floor: OnEditor::default(),
 
-            current_floor_layout: HashMap::new(),
+            current_floor_layout: FloorLayout::new(),
             estimated_completion_time: 0.0,
 
+            player_properties: PlayerProperties::default(),
+
+            completion_rules: CompletionRules::default(),
+
             base,
         }
     }
 
     fn process(&mut self, _delta: f64) {
-        if let Some(ref floor_generation_callback) = self.floor_generation_thread
-            && floor_generation_callback.is_finished()
+        if let Some(ref floor_generation_job) = self.floor_generation_job
+            && floor_generation_job.is_finished()
         {
-            let result = self.floor_generation_thread.take();
+            let result = self.floor_generation_job.take();
 
             match result.unwrap().join() {
-                Ok((floor_layout, estimated_completion_time)) => {
+                Ok(Ok((floor_layout, estimated_completion_time))) => {
                     godot_print!("Success generating floor");
 
                     self.current_floor_layout = floor_layout;
                     self.estimated_completion_time = estimated_completion_time;
 
-                    self.current_floor
-                        .bind_mut()
-                        .load_floor(self.current_floor_layout.clone());
+                    self.current_floor.bind_mut().load_floor(
+                        self.current_floor_layout.clone(),
+                        &self.player_properties,
+                        &self.completion_rules.section_weights,
+                    );
 
                     self.signals().level_setup_complete().emit();
                 }
-                Err(err) => {
-                    godot_error!("Error generating floor: {err:?}");
+                Ok(Err(message)) => {
+                    godot_error!("Error loading floor: {message}");
+
+                    self.signals().level_setup_failed().emit(&message);
+                }
+                Err(panic) => {
+                    let message = get_panic_message(&*panic);
+
+                    godot_error!("Error generating floor: {message}");
 
-                    self.signals().level_setup_failed().emit();
+                    self.signals()
+                        .level_setup_failed()
+                        .emit(&format!("floor generation crashed: {message}"));
                 }
             }
         }
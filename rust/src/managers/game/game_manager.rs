@@ -1,15 +1,31 @@
-use crate::managers::game::floor_manager::{FloorLayout, FloorManager};
+use crate::managers::game::floor_manager::FloorManager;
 use crate::managers::game::game_ui_manager::{GameUIManager, GameUIView};
 use crate::managers::save_manager::SaveManager;
+use crate::objects::ghost::Ghost;
 use crate::objects::player::Player;
+use crate::types::completion_rules::CompletionRules;
+use crate::types::floor_layout::FloorLayout;
+use crate::types::ghost::GhostTrace;
 use crate::types::input_bindings::InputBindings;
+use crate::types::replay::Replay;
 use crate::types::save_game::SaveGame;
-use godot::classes::{INode, InputEvent, Node};
+use crate::types::simulation::{
+    SIMULATION_TICK_RATE, TickAccumulator, seconds_to_ticks, ticks_to_seconds,
+};
+use godot::classes::file_access::ModeFlags;
+use godot::classes::notify::NodeNotification;
+use godot::classes::{DirAccess, FileAccess, INode, InputEvent, Node, Os, Time};
 use godot::prelude::*;
+use std::sync::Mutex;
 
 const MAX_MONEY_GAIN: i64 = 30;
 const MAX_MONEY_LOSS: i64 = -20;
 
+pub const REPLAY_DIRECTORY: &str = "user://replays";
+
+// outlives the main menu so the game scene can pick up a replay chosen there
+static QUEUED_REPLAY: Mutex<Option<String>> = Mutex::new(None);
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum GameState {
     Loading,
@@ -17,6 +33,8 @@ pub enum GameState {
     Running,
     Scoring,
     Paused,
+    // counting down back into the state the game was paused in
+    Resuming,
 }
 
 #[derive(GodotClass)]
@@ -31,19 +49,42 @@ pub struct GameManager {
     #[export]
     player: OnEditor<Gd<Player>>,
     #[export]
+    ghost: OnEditor<Gd<Ghost>>,
+    #[export]
     floor_manager: OnEditor<Gd<FloorManager>>,
 
+    // seconds counted down before play picks up again after a pause, 0 resumes straight away
+    #[export]
+    resume_countdown: f64,
+
     pub game_state: GameState,
 
+    // the state to go back to when the pause ends
+    paused_state: Option<GameState>,
+    resume_timer: f64,
+
     pub current_game: SaveGame,
 
     floor_layout: FloorLayout,
 
     estimated_completion_time: f64,
 
+    completion_rules: CompletionRules,
+
+    collected_coins: i64,
+
+    // set when watching a replay, nothing is saved while it plays
+    replay: Option<Replay>,
+
+    ghost_recording: GhostTrace,
+    best_ghost: Option<GhostTrace>,
+
+    // whole simulation ticks, only turned into seconds for display and scoring
     run_timer: bool,
-    warmup_timer: f64,
-    game_timer: f64,
+    warmup_ticks: i64,
+    game_ticks: i64,
+
+    tick_accumulator: TickAccumulator,
 
     base: Base<Node>,
 }
@@ -52,9 +93,11 @@ pub struct GameManager {
 impl GameManager {
     #[func]
     fn save_and_exit(&mut self) {
-        self.save_manager
-            .bind_mut()
-            .update_save_game(Some(self.current_game.clone()));
+        if self.replay.is_none() {
+            self.save_manager
+                .bind_mut()
+                .update_save_game(Some(self.current_game.clone()));
+        }
 
         if let Some(ref mut scene_tree) = self.base().get_tree() {
             scene_tree.change_scene_to_file("res://scenes/main_menu/main_menu.tscn");
@@ -63,13 +106,33 @@ impl GameManager {
 
     #[func]
     fn clear_and_exit(&mut self) {
-        self.save_manager.bind_mut().update_save_game(None);
+        if self.replay.is_none() {
+            self.save_manager.bind_mut().update_save_game(None);
+        }
 
         if let Some(ref mut scene_tree) = self.base().get_tree() {
             scene_tree.change_scene_to_file("res://scenes/main_menu/main_menu.tscn");
         }
     }
 
+    #[func]
+    fn resume_game(&mut self) {
+        if self.game_state != GameState::Paused {
+            return;
+        }
+
+        let Some(ref paused_state) = self.paused_state else {
+            return;
+        };
+
+        // nothing is running while loading, so there is nothing to count down into
+        if self.resume_countdown > 0.0 && *paused_state != GameState::Loading {
+            self.change_game_state(GameState::Resuming);
+        } else {
+            self.finish_resume();
+        }
+    }
+
     #[func]
     fn restart_level(&mut self) {
         self.change_game_state(GameState::Loading);
@@ -77,6 +140,13 @@ impl GameManager {
 
     #[func]
     fn start_shop(&mut self) {
+        // a replay has no run to carry on with
+        if self.replay.is_some() {
+            self.save_and_exit();
+
+            return;
+        }
+
         self.save_manager
             .bind_mut()
             .update_save_game(Some(self.current_game.clone()));
@@ -91,8 +161,12 @@ impl GameManager {
 
         match next_game_state {
             GameState::Loading => {
+                self.paused_state = None;
+
                 self.setup_level();
 
+                self.game_ui.bind_mut().reset_loading();
+
                 self.game_ui
                     .bind_mut()
                     .set_floor(self.current_game.current_floor);
@@ -101,7 +175,13 @@ impl GameManager {
             }
             GameState::WarmUp => {
                 self.run_timer = true;
-                self.warmup_timer = self.current_game.player_properties.warmup_time;
+                self.warmup_ticks =
+                    seconds_to_ticks(self.current_game.player_properties.warmup_time);
+
+                self.tick_accumulator.reset();
+
+                self.collected_coins = 0;
+                self.game_ui.bind_mut().set_collected_coins(0);
 
                 self.game_ui
                     .bind_mut()
@@ -110,11 +190,27 @@ impl GameManager {
             }
             GameState::Running => {
                 self.run_timer = true;
-                self.game_timer = 0.0;
+                self.game_ticks = 0;
+
+                self.tick_accumulator.reset();
 
                 self.game_ui.bind_mut().open_ui_view(GameUIView::Playing);
             }
             GameState::Scoring => {
+                match self.replay {
+                    Some(ref replay) => {
+                        godot_print!(
+                            "replay finished in {} ticks, recorded as {} ticks",
+                            self.game_ticks,
+                            replay.final_ticks
+                        );
+                    }
+                    None => {
+                        self.save_replay();
+                        self.save_ghost();
+                    }
+                }
+
                 let game_over = self.score_run();
 
                 self.game_ui
@@ -122,8 +218,21 @@ impl GameManager {
                     .open_ui_view(GameUIView::Scoring(game_over));
             }
             GameState::Paused => {
+                // pausing again during the resume countdown keeps the original state
+                if self.game_state != GameState::Resuming {
+                    self.paused_state = Some(self.game_state.clone());
+                }
+
                 self.game_ui.bind_mut().open_ui_view(GameUIView::Paused);
             }
+            GameState::Resuming => {
+                self.resume_timer = self.resume_countdown;
+
+                self.game_ui.bind_mut().open_ui_view(GameUIView::Countdown);
+                self.game_ui
+                    .bind_mut()
+                    .set_resume_countdown(self.resume_timer);
+            }
         }
 
         self.game_state = next_game_state.clone();
@@ -131,16 +240,60 @@ impl GameManager {
         self.player.bind_mut().change_game_state(next_game_state);
     }
 
+    // goes back to the paused state as it was, without starting it over
+    fn finish_resume(&mut self) {
+        let Some(paused_state) = self.paused_state.take() else {
+            return;
+        };
+
+        godot_print!("resuming game state: {paused_state:?}");
+
+        let view = match paused_state {
+            GameState::Loading => GameUIView::Loading,
+            GameState::WarmUp => GameUIView::Countdown,
+            _ => GameUIView::Playing,
+        };
+
+        self.game_ui.bind_mut().open_ui_view(view);
+
+        // the time spent paused is never caught up on
+        self.tick_accumulator.reset();
+
+        self.game_state = paused_state.clone();
+
+        self.player.bind_mut().change_game_state(paused_state);
+    }
+
+    #[func]
+    fn toggle_minimap(&mut self) {
+        // kept in the save so the choice sticks between floors
+        self.current_game.show_minimap = !self.current_game.show_minimap;
+
+        self.game_ui
+            .bind_mut()
+            .set_minimap_shown(self.current_game.show_minimap);
+    }
+
     fn setup_level(&mut self) {
+        // read every time so the rules can be tuned between restarts
+        let completion_rules = FloorManager::read_completion_rules();
+
+        // a replay ends where it did when it was recorded, whatever the rules are now
+        let completion_rules = match self.replay {
+            Some(ref replay) => replay.get_completion_rules(&completion_rules),
+            None => completion_rules,
+        };
+
         self.floor_manager
             .bind_mut()
-            .setup_level(self.current_game.clone());
+            .setup_level(self.current_game.clone(), completion_rules);
     }
 
     #[func]
     pub fn level_setup_complete(&mut self) {
         self.floor_layout = self.floor_manager.bind().current_floor_layout.clone();
         self.estimated_completion_time = self.floor_manager.bind().estimated_completion_time;
+        self.completion_rules = self.floor_manager.bind().completion_rules.clone();
 
         self.player
             .bind_mut()
@@ -148,19 +301,248 @@ impl GameManager {
 
         self.player.bind_mut().reset();
 
+        self.ghost_recording = GhostTrace::new(
+            self.current_game.level_seed,
+            self.current_game.current_floor,
+            &self.completion_rules,
+        );
+        self.best_ghost = Self::read_best_ghost(
+            self.current_game.level_seed,
+            self.current_game.current_floor,
+        )
+        .filter(|best_ghost| best_ghost.was_played_under(&self.completion_rules));
+
+        self.ghost.bind_mut().set_trace(self.best_ghost.clone());
+        self.game_ui.bind_mut().set_ghost_delta(None);
+        self.game_ui.bind_mut().clear_input_history();
+
+        let was_paused = self.game_state == GameState::Paused;
+
         self.change_game_state(GameState::WarmUp);
+
+        // a floor that finishes loading behind the pause menu waits there to be resumed
+        if was_paused {
+            self.change_game_state(GameState::Paused);
+        }
     }
 
     #[func]
-    pub fn level_setup_failed(&mut self) {
-        self.change_game_state(GameState::Loading);
+    pub fn level_setup_failed(&mut self, message: GString) {
+        // every generator including the fallback failed, so stay on the loading
+        // screen and let the player restart or leave from the pause menu
+        self.game_ui
+            .bind_mut()
+            .set_loading_error(message.to_string());
+    }
+
+    fn save_replay(&mut self) {
+        let inputs = self.player.bind_mut().take_recorded_inputs();
+
+        let replay = Replay::new(
+            &self.current_game,
+            &self.completion_rules,
+            self.game_ticks,
+            inputs,
+        );
+
+        let _ = DirAccess::make_dir_recursive_absolute(REPLAY_DIRECTORY);
+
+        let path = format!(
+            "{REPLAY_DIRECTORY}/{}",
+            replay.get_file_name(Time::singleton().get_unix_time_from_system() as i64)
+        );
+
+        match FileAccess::open(&path, ModeFlags::WRITE) {
+            Some(mut file) => {
+                file.store_string(&replay.to_json());
+                file.close();
+
+                godot_print!("saved replay to {path}");
+            }
+            None => godot_error!("failed to save replay to {path}"),
+        }
+    }
+
+    fn get_ghost_path(level_seed: i64, floor: i64) -> String {
+        format!(
+            "user://ghosts/{}",
+            GhostTrace::get_file_name(level_seed, floor)
+        )
+    }
+
+    fn read_best_ghost(level_seed: i64, floor: i64) -> Option<GhostTrace> {
+        let path = Self::get_ghost_path(level_seed, floor);
+
+        if !FileAccess::file_exists(&path) {
+            return None;
+        }
+
+        match GhostTrace::parse(&FileAccess::get_file_as_string(&path).to_string()) {
+            Ok(ghost) => Some(ghost),
+            Err(err) => {
+                godot_error!("failed to load ghost {path}: {err}");
+
+                None
+            }
+        }
+    }
+
+    // only a new personal best replaces the stored ghost
+    fn save_ghost(&mut self) {
+        self.ghost_recording.finish(self.get_game_time());
+
+        if let Some(ref best_ghost) = self.best_ghost
+            && !self.ghost_recording.is_better_than(best_ghost)
+        {
+            return;
+        }
+
+        let _ = DirAccess::make_dir_recursive_absolute("user://ghosts");
+
+        let path =
+            Self::get_ghost_path(self.ghost_recording.level_seed, self.ghost_recording.floor);
+
+        match FileAccess::open(&path, ModeFlags::WRITE) {
+            Some(mut file) => {
+                file.store_string(&self.ghost_recording.to_json());
+                file.close();
+            }
+            None => godot_error!("failed to save ghost to {path}"),
+        }
+    }
+
+    // the next game scene plays this replay instead of the save game
+    pub fn queue_replay(path: String) {
+        match QUEUED_REPLAY.lock() {
+            Ok(mut queued_replay) => *queued_replay = Some(path),
+            Err(err) => godot_error!("failed to lock queued replay: {err}"),
+        }
+    }
+
+    // one picked in the main menu, or one passed on the command line as --replay=<path>
+    fn read_replay() -> Option<Replay> {
+        let queued_replay = match QUEUED_REPLAY.lock() {
+            Ok(mut queued_replay) => queued_replay.take(),
+            Err(err) => {
+                godot_error!("failed to lock queued replay: {err}");
+
+                None
+            }
+        };
+
+        let path = queued_replay.or_else(|| {
+            Os::singleton()
+                .get_cmdline_user_args()
+                .as_slice()
+                .iter()
+                .find_map(|arg| arg.to_string().strip_prefix("--replay=").map(str::to_owned))
+        })?;
+
+        if !FileAccess::file_exists(&path) {
+            godot_error!("replay {path} does not exist");
+
+            return None;
+        }
+
+        match Replay::parse(&FileAccess::get_file_as_string(&path).to_string()) {
+            Ok(replay) => {
+                if replay.tick_rate != SIMULATION_TICK_RATE {
+                    godot_warn!(
+                        "replay was recorded at {} ticks per second but the game runs at {SIMULATION_TICK_RATE}, it will not play back the same",
+                        replay.tick_rate
+                    );
+                }
+
+                Some(replay)
+            }
+            Err(err) => {
+                godot_error!("failed to load replay {path}: {err}");
+
+                None
+            }
+        }
+    }
+
+    fn get_completion_threshold(&self) -> f64 {
+        self.completion_rules
+            .get_threshold(self.current_game.current_floor)
+    }
+
+    fn get_game_time(&self) -> f64 {
+        ticks_to_seconds(self.game_ticks)
+    }
+
+    // one fixed step of the floor, the player moves first and the fog catches up after
+    fn simulate_tick(&mut self) {
+        match self.game_state {
+            GameState::WarmUp => {
+                self.floor_manager.bind_mut().simulate_tick();
+
+                if self.run_timer {
+                    self.warmup_ticks -= 1;
+                }
+
+                if self.warmup_ticks <= 0 {
+                    self.run_timer = false;
+                }
+            }
+            GameState::Running => {
+                // nothing moves once the floor is finished, so the time is the same every replay
+                if !self.run_timer {
+                    return;
+                }
+
+                self.game_ticks += 1;
+
+                self.player.bind_mut().simulate_tick();
+
+                let turned = self.player.bind().get_movement_state().turned;
+
+                if let Some(turn) = turned {
+                    self.game_ui.bind_mut().push_input_history(turn);
+                }
+
+                self.floor_manager.bind_mut().simulate_tick();
+
+                let complete_progress = self.floor_manager.bind().get_completion_progress();
+
+                let player_position = self.player.get_position();
+
+                self.ghost_recording.record(
+                    self.get_game_time(),
+                    (player_position.x as f64, player_position.y as f64),
+                    complete_progress,
+                );
+
+                let collected_coins = self.floor_manager.bind().get_collected_coins();
+
+                if collected_coins != self.collected_coins {
+                    self.collected_coins = collected_coins;
+
+                    self.game_ui.bind_mut().set_collected_coins(collected_coins);
+                }
+
+                let finish_requested = self.player.bind().is_finish_requested();
+
+                // past the threshold the floor counts as done, the player can leave or keep
+                // exploring for the full reveal bonus
+                if complete_progress >= 1.0
+                    || (finish_requested && complete_progress >= self.get_completion_threshold())
+                {
+                    self.run_timer = false;
+                }
+            }
+            _ => {}
+        }
     }
 
     fn score_run(&mut self) -> bool {
         self.current_game.in_shop = true;
 
+        let game_time = self.get_game_time();
+
         let logical_estimated_time = self.estimated_completion_time.max(0.1);
-        let logical_completion_time = self.game_timer.max(0.1);
+        let logical_completion_time = game_time.max(0.1);
 
         let money_delta = if self.current_game.current_floor == 0 {
             10
@@ -172,12 +554,18 @@ impl GameManager {
 
         let starting_money = self.current_game.money;
 
-        let ending_money = starting_money + money_delta;
+        let full_reveal_bonus = if self.floor_manager.bind().get_completion_progress() >= 1.0 {
+            self.completion_rules.full_reveal_bonus
+        } else {
+            0
+        };
+
+        let ending_money = starting_money + money_delta + self.collected_coins + full_reveal_bonus;
 
         self.current_game.money = ending_money;
 
         self.game_ui.bind_mut().set_scores(
-            self.game_timer,
+            game_time,
             self.estimated_completion_time,
             starting_money,
             ending_money,
@@ -185,6 +573,10 @@ impl GameManager {
 
         self.current_game.current_floor += 1;
 
+        if self.replay.is_some() {
+            return false;
+        }
+
         if ending_money <= 0 {
             self.save_manager.bind_mut().update_save_game(None);
 
@@ -208,26 +600,51 @@ impl INode for GameManager {
             game_ui: OnEditor::default(),
 
             player: OnEditor::default(),
+            ghost: OnEditor::default(),
             floor_manager: OnEditor::default(),
 
+            resume_countdown: 3.0,
+
             game_state: GameState::Loading,
 
+            paused_state: None,
+            resume_timer: 0.0,
+
             current_game: SaveGame::new(0),
 
             floor_layout: FloorLayout::new(),
 
             estimated_completion_time: 0.0,
 
+            completion_rules: CompletionRules::default(),
+
+            collected_coins: 0,
+
+            replay: None,
+
+            ghost_recording: GhostTrace::new(0, 0, &CompletionRules::default()),
+            best_ghost: None,
+
             run_timer: false,
-            warmup_timer: 0.0,
-            game_timer: 0.0,
+            warmup_ticks: 0,
+            game_ticks: 0,
+
+            tick_accumulator: TickAccumulator::default(),
 
             base,
         }
     }
 
     fn ready(&mut self) {
-        if let Some(ref save_file) = self.save_manager.bind().save_game {
+        self.replay = Self::read_replay();
+
+        if let Some(ref replay) = self.replay {
+            self.current_game = replay.get_save_game();
+
+            self.player
+                .bind_mut()
+                .set_playback(Some(replay.inputs.to_ticks()));
+        } else if let Some(ref save_file) = self.save_manager.bind().save_game {
             godot_print!("{save_file:?}");
 
             self.current_game = save_file.clone();
@@ -237,14 +654,27 @@ impl INode for GameManager {
             }
         }
 
+        self.game_ui
+            .bind_mut()
+            .set_minimap_shown(self.current_game.show_minimap);
+
         self.change_game_state(GameState::Loading);
     }
 
-    fn process(&mut self, _delta: f64) {
+    fn process(&mut self, delta: f64) {
         match self.game_state {
+            GameState::Loading => {
+                let generation_progress = self.floor_manager.bind().get_generation_progress();
+
+                if let Some(generation_progress) = generation_progress {
+                    self.game_ui
+                        .bind_mut()
+                        .set_loading_progress(generation_progress);
+                }
+            }
             GameState::WarmUp => {
                 self.game_ui.bind_mut().set_countdown_progress(
-                    self.warmup_timer,
+                    ticks_to_seconds(self.warmup_ticks),
                     self.current_game.player_properties.warmup_time,
                 );
 
@@ -253,54 +683,95 @@ impl INode for GameManager {
                 }
             }
             GameState::Running => {
+                let game_time = self.get_game_time();
+
                 self.game_ui
                     .bind_mut()
-                    .set_game_time(self.game_timer, self.estimated_completion_time);
+                    .set_game_time(game_time, self.estimated_completion_time);
 
-                if !self.run_timer {
-                    self.change_game_state(GameState::Scoring);
-                }
-            }
-            _ => {}
-        }
-    }
+                let complete_progress = self.floor_manager.bind().get_completion_progress();
 
-    fn physics_process(&mut self, delta: f64) {
-        match self.game_state {
-            GameState::WarmUp => {
-                if self.run_timer {
-                    self.warmup_timer -= delta;
-                }
+                self.game_ui
+                    .bind_mut()
+                    .set_exploration_progress(complete_progress);
 
-                if self.warmup_timer <= 0.0 {
-                    self.run_timer = false;
+                let is_floor_cleared = complete_progress >= self.get_completion_threshold();
+
+                self.game_ui
+                    .bind_mut()
+                    .set_floor_cleared(is_floor_cleared && complete_progress < 1.0);
+
+                self.ghost.bind_mut().set_time(game_time);
+
+                let ghost_delta = self.best_ghost.as_ref().map(|best_ghost| {
+                    game_time - best_ghost.get_time_at_progress(complete_progress)
+                });
+
+                self.game_ui.bind_mut().set_ghost_delta(ghost_delta);
+
+                if !self.run_timer {
+                    self.change_game_state(GameState::Scoring);
                 }
             }
-            GameState::Running => {
-                self.game_timer += delta;
-
-                let complete_progress = self.floor_manager.bind().get_completion_progress();
+            GameState::Resuming => {
+                self.resume_timer -= delta;
 
                 self.game_ui
                     .bind_mut()
-                    .set_exploration_progress(complete_progress);
+                    .set_resume_countdown(self.resume_timer);
 
-                if complete_progress >= 1.0 {
-                    self.run_timer = false;
+                if self.resume_timer <= 0.0 {
+                    self.finish_resume();
                 }
             }
             _ => {}
         }
     }
 
+    fn physics_process(&mut self, delta: f64) {
+        if self.game_state != GameState::WarmUp && self.game_state != GameState::Running {
+            return;
+        }
+
+        for _ in 0..self.tick_accumulator.advance(delta) {
+            self.simulate_tick();
+        }
+    }
+
     fn input(&mut self, event: Gd<InputEvent>) {
         if event
+            .is_action_pressed_ex(Into::<&str>::into(InputBindings::GameToggleMinimap))
+            .allow_echo(false)
+            .exact_match(true)
+            .done()
+        {
+            self.toggle_minimap();
+
+            return;
+        }
+
+        if !event
             .is_action_pressed_ex(Into::<&str>::into(InputBindings::GamePause))
             .allow_echo(false)
             .exact_match(true)
             .done()
-            && self.game_state != GameState::Scoring
-            && self.game_state != GameState::Paused
+        {
+            return;
+        }
+
+        match self.game_state {
+            GameState::Paused => self.resume_game(),
+            GameState::Scoring => {}
+            _ => self.change_game_state(GameState::Paused),
+        }
+    }
+
+    fn on_notification(&mut self, what: NodeNotification) {
+        if what == NodeNotification::APPLICATION_FOCUS_OUT
+            && matches!(
+                self.game_state,
+                GameState::WarmUp | GameState::Running | GameState::Resuming
+            )
         {
             self.change_game_state(GameState::Paused);
         }
@@ -1,6 +1,20 @@
-use godot::classes::{CanvasItem, INode, Label, Node, TextureProgressBar};
+use crate::objects::map::minimap::Minimap;
+use crate::types::movement::{
+    DOWN_DIRECTION, LEFT_DIRECTION, MovementTurn, RIGHT_DIRECTION, UP_DIRECTION,
+};
+use godot::classes::texture_rect::{ExpandMode, StretchMode};
+use godot::classes::{
+    CanvasItem, Control, HBoxContainer, INode, Label, Node, ProgressBar, ScrollContainer,
+    Texture2D, TextureProgressBar, TextureRect, VBoxContainer,
+};
+use godot::global::HorizontalAlignment;
 use godot::prelude::*;
 
+// older inputs drop off the front of the strip
+const MAX_INPUT_HISTORY: usize = 32;
+
+const INPUT_ARROW_SIZE: f32 = 24.0;
+
 pub enum GameUIView {
     Loading,
     Countdown,
@@ -16,6 +30,10 @@ pub struct GameUIManager {
 
     #[export]
     loading_root: OnEditor<Gd<CanvasItem>>,
+    #[export]
+    loading_label: OnEditor<Gd<Label>>,
+    #[export]
+    loading_progress_bar: OnEditor<Gd<ProgressBar>>,
 
     #[export]
     countdown_root: OnEditor<Gd<CanvasItem>>,
@@ -30,6 +48,8 @@ pub struct GameUIManager {
 
     #[export]
     progress_ring: OnEditor<Gd<TextureProgressBar>>,
+    #[export]
+    finish_hint: OnEditor<Gd<CanvasItem>>,
 
     #[export]
     score_root: OnEditor<Gd<CanvasItem>>,
@@ -44,6 +64,25 @@ pub struct GameUIManager {
     game_time_label: OnEditor<Gd<Label>>,
     #[export]
     target_time_label: OnEditor<Gd<Label>>,
+    #[export]
+    ghost_delta_label: OnEditor<Gd<Label>>,
+
+    #[export]
+    coin_label: OnEditor<Gd<Label>>,
+
+    #[export]
+    input_root: OnEditor<Gd<CanvasItem>>,
+    #[export]
+    input_scroll: OnEditor<Gd<ScrollContainer>>,
+    #[export]
+    input_list: OnEditor<Gd<HBoxContainer>>,
+
+    input_arrow_texture: Gd<Texture2D>,
+
+    #[export]
+    map_view: OnEditor<Gd<Control>>,
+    #[export]
+    minimap: OnEditor<Gd<Minimap>>,
 
     #[export]
     score_floor_number_label: OnEditor<Gd<Label>>,
@@ -72,6 +111,9 @@ pub struct GameUIManager {
 
 impl GameUIManager {
     pub fn open_ui_view(&mut self, view: GameUIView) {
+        // only ever turned on while playing
+        self.finish_hint.set_visible(false);
+
         match view {
             GameUIView::Loading => {
                 self.loading_root.set_visible(true);
@@ -81,6 +123,8 @@ impl GameUIManager {
                 self.score_root.set_visible(false);
 
                 self.pause_root.set_visible(false);
+
+                self.input_root.set_visible(false);
             }
             GameUIView::Countdown => {
                 self.loading_root.set_visible(false);
@@ -90,6 +134,8 @@ impl GameUIManager {
                 self.score_root.set_visible(false);
 
                 self.pause_root.set_visible(false);
+
+                self.input_root.set_visible(true);
             }
             GameUIView::Playing => {
                 self.loading_root.set_visible(false);
@@ -103,6 +149,8 @@ impl GameUIManager {
                 self.score_root.set_visible(false);
 
                 self.pause_root.set_visible(false);
+
+                self.input_root.set_visible(true);
             }
             GameUIView::Scoring(game_over) => {
                 self.loading_root.set_visible(false);
@@ -115,6 +163,8 @@ impl GameUIManager {
                 self.score_continue_button.set_visible(!game_over);
 
                 self.pause_root.set_visible(false);
+
+                self.input_root.set_visible(true);
             }
             GameUIView::Paused => {
                 self.loading_root.set_visible(false);
@@ -124,16 +174,42 @@ impl GameUIManager {
                 self.score_root.set_visible(false);
 
                 self.pause_root.set_visible(true);
+
+                self.input_root.set_visible(false);
             }
         }
 
         self.view = view;
     }
 
+    // kept out of open_ui_view so a failed load still shows its error after a pause
+    pub fn reset_loading(&mut self) {
+        self.loading_label.set_text("loading floor...");
+        self.loading_progress_bar.set_value(0.0);
+    }
+
+    pub fn set_loading_progress(&mut self, progress: f64) {
+        self.loading_progress_bar
+            .set_value(progress.clamp(0.0, 1.0) * 100.0);
+    }
+
+    pub fn set_loading_error(&mut self, message: String) {
+        self.loading_label
+            .set_text(&format!("failed to load floor\n{message}"));
+    }
+
     pub fn set_countdown_progress(&mut self, time: f64, duration: f64) {
         self.progress_ring
             .set_value((time / duration).clamp(0.0, 1.0) * 100.0);
 
+        self.set_countdown_number(time);
+    }
+
+    pub fn set_resume_countdown(&mut self, time: f64) {
+        self.set_countdown_number(time);
+    }
+
+    fn set_countdown_number(&mut self, time: f64) {
         match time {
             x if x <= 1.0 => {
                 self.countdown_big_3.set_visible(false);
@@ -177,11 +253,114 @@ impl GameUIManager {
             .set_text(&Self::get_formatted_time(target));
     }
 
+    // how far ahead (negative) or behind the personal best ghost the player is
+    pub fn set_ghost_delta(&mut self, delta: Option<f64>) {
+        let Some(delta) = delta else {
+            self.ghost_delta_label.set_visible(false);
+
+            return;
+        };
+
+        self.ghost_delta_label.set_visible(true);
+        self.ghost_delta_label.set_text(&format!("{delta:+.2}"));
+        self.ghost_delta_label.set_modulate(if delta <= 0.0 {
+            Color::from_rgb(0.5, 1.0, 0.5)
+        } else {
+            Color::from_rgb(1.0, 0.5, 0.5)
+        });
+    }
+
+    pub fn clear_input_history(&mut self) {
+        for child in self.input_list.get_children().iter_shared() {
+            self.remove_input_history_entry(child);
+        }
+    }
+
+    // taken out of the list straight away, a queued free would still count towards the cap
+    fn remove_input_history_entry(&mut self, mut entry: Gd<Node>) {
+        self.input_list.remove_child(&entry);
+
+        entry.queue_free();
+    }
+
+    // one arrow per direction the player set off in, with when it was pressed underneath
+    pub fn push_input_history(&mut self, turn: MovementTurn) {
+        let mut entry = VBoxContainer::new_alloc();
+
+        // clean turns kept their momentum, the rest cost a stop cooldown
+        entry.set_modulate(if turn.had_cooldown {
+            Color::from_rgb(1.0, 0.6, 0.4)
+        } else {
+            Color::from_rgb(0.5, 1.0, 0.5)
+        });
+
+        let mut arrow = TextureRect::new_alloc();
+
+        arrow.set_texture(&self.input_arrow_texture);
+        arrow.set_expand_mode(ExpandMode::IGNORE_SIZE);
+        arrow.set_stretch_mode(StretchMode::KEEP_ASPECT_CENTERED);
+        arrow.set_custom_minimum_size(Vector2::new(INPUT_ARROW_SIZE, INPUT_ARROW_SIZE));
+        arrow.set_pivot_offset(Vector2::new(INPUT_ARROW_SIZE, INPUT_ARROW_SIZE) / 2.0);
+        arrow.set_rotation_degrees(match turn.direction {
+            UP_DIRECTION => 0.0,
+            RIGHT_DIRECTION => 90.0,
+            DOWN_DIRECTION => 180.0,
+            LEFT_DIRECTION => 270.0,
+            _ => 0.0,
+        });
+
+        entry.add_child(&arrow);
+
+        let mut timing = Label::new_alloc();
+
+        timing.set_text(&match turn.press_offset {
+            Some(press_offset) => format!("{press_offset:+.2}"),
+            None => "-".to_string(),
+        });
+        timing.set_horizontal_alignment(HorizontalAlignment::CENTER);
+
+        entry.add_child(&timing);
+
+        self.input_list.add_child(&entry);
+
+        let children = self.input_list.get_children();
+
+        for child in children
+            .iter_shared()
+            .take(children.len().saturating_sub(MAX_INPUT_HISTORY))
+        {
+            self.remove_input_history_entry(child);
+        }
+    }
+
+    fn on_input_scroll_changed(&mut self) {
+        if let Some(mut scroll_bar) = self.input_scroll.get_h_scroll_bar() {
+            let max = scroll_bar.get_max();
+
+            scroll_bar.set_value(max);
+        }
+    }
+
+    // hiding the map view lets the split container give the player view the whole screen
+    pub fn set_minimap_shown(&mut self, show_minimap: bool) {
+        self.map_view.set_visible(!show_minimap);
+        self.minimap.set_visible(show_minimap);
+    }
+
     pub fn set_exploration_progress(&mut self, progress: f64) {
         self.progress_ring
             .set_value(progress.clamp(0.0, 1.0) * 100.0);
     }
 
+    // shown while the floor could be finished but has not been seen in full yet
+    pub fn set_floor_cleared(&mut self, is_cleared: bool) {
+        self.finish_hint.set_visible(is_cleared);
+    }
+
+    pub fn set_collected_coins(&mut self, coins: i64) {
+        self.coin_label.set_text(&format!("${coins}"));
+    }
+
     pub fn set_scores(&mut self, time: f64, target: f64, start_money: i64, end_money: i64) {
         let delta_time = target - time;
 
@@ -236,6 +415,8 @@ impl INode for GameUIManager {
             view: GameUIView::Loading,
 
             loading_root: OnEditor::default(),
+            loading_label: OnEditor::default(),
+            loading_progress_bar: OnEditor::default(),
 
             countdown_root: OnEditor::default(),
             countdown_big_3: OnEditor::default(),
@@ -244,6 +425,7 @@ impl INode for GameUIManager {
             countdown_big_go: OnEditor::default(),
 
             progress_ring: OnEditor::default(),
+            finish_hint: OnEditor::default(),
 
             score_root: OnEditor::default(),
 
@@ -253,6 +435,18 @@ impl INode for GameUIManager {
 
             game_time_label: OnEditor::default(),
             target_time_label: OnEditor::default(),
+            ghost_delta_label: OnEditor::default(),
+
+            coin_label: OnEditor::default(),
+
+            input_root: OnEditor::default(),
+            input_scroll: OnEditor::default(),
+            input_list: OnEditor::default(),
+
+            input_arrow_texture: load::<Texture2D>("res://icons/input-arrow.png"),
+
+            map_view: OnEditor::default(),
+            minimap: OnEditor::default(),
 
             score_floor_number_label: OnEditor::default(),
             score_game_time_label: OnEditor::default(),
@@ -272,5 +466,13 @@ impl INode for GameUIManager {
 
     fn ready(&mut self) {
         self.open_ui_view(GameUIView::Loading);
+
+        // the strip grows to the right, so follow it to keep the newest input in view
+        if let Some(scroll_bar) = self.input_scroll.get_h_scroll_bar() {
+            scroll_bar
+                .signals()
+                .changed()
+                .connect_other(self, Self::on_input_scroll_changed);
+        }
     }
 }
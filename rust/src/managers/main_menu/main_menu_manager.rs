@@ -1,7 +1,8 @@
+use crate::managers::game::game_manager::{GameManager, REPLAY_DIRECTORY};
 use crate::managers::main_menu::main_menu_ui_manager::{MainMenuUIManager, MenuUIView};
 use crate::managers::save_manager::SaveManager;
 use crate::types::save_game::SaveGame;
-use godot::classes::{INode, Node, Time};
+use godot::classes::{DirAccess, FileAccess, INode, Node, Time};
 use godot::global::{randi, seed};
 use godot::prelude::*;
 
@@ -14,6 +15,9 @@ struct MainMenuManager {
     #[export]
     main_menu_ui: OnEditor<Gd<MainMenuUIManager>>,
 
+    // file names in the replay directory, in the order the list shows them
+    replay_names: Vec<String>,
+
     base: Base<Node>,
 }
 
@@ -31,6 +35,52 @@ impl MainMenuManager {
         self.main_menu_ui.bind_mut().open_ui_view(MenuUIView::Home);
     }
 
+    #[func]
+    fn open_replay_menu(&mut self) {
+        let mut replay_names: Vec<String> = DirAccess::get_files_at(REPLAY_DIRECTORY)
+            .as_slice()
+            .iter()
+            .map(|name| name.to_string())
+            .filter(|name| name.ends_with(".replay"))
+            .collect();
+
+        // newest first
+        replay_names.sort_by_key(|name| {
+            std::cmp::Reverse(FileAccess::get_modified_time(&format!(
+                "{REPLAY_DIRECTORY}/{name}"
+            )))
+        });
+
+        self.main_menu_ui.bind_mut().set_replays(&replay_names);
+        self.main_menu_ui
+            .bind_mut()
+            .open_ui_view(MenuUIView::Replays);
+
+        self.replay_names = replay_names;
+    }
+
+    #[func]
+    fn watch_selected_replay(&mut self) {
+        let selected_replay = self.main_menu_ui.bind_mut().get_selected_replay();
+
+        if let Some(index) = selected_replay {
+            self.watch_replay(index as i64);
+        }
+    }
+
+    #[func]
+    fn watch_replay(&mut self, index: i64) {
+        let Some(replay_name) = self.replay_names.get(index as usize) else {
+            return;
+        };
+
+        GameManager::queue_replay(format!("{REPLAY_DIRECTORY}/{replay_name}"));
+
+        if let Some(ref mut scene_tree) = self.base().get_tree() {
+            scene_tree.change_scene_to_file("res://scenes/game/game.tscn");
+        }
+    }
+
     #[func]
     fn reroll_seed(&mut self) {
         let new_seed = randi();
@@ -42,9 +92,14 @@ impl MainMenuManager {
     fn start_game(&mut self) {
         let new_game_seed = self.main_menu_ui.bind_mut().get_seed();
 
+        let mut new_game = SaveGame::new(new_game_seed);
+
+        // the accessibility option starts the run with the buffer the shop would otherwise sell
+        new_game.player_properties.has_move_buffer = self.main_menu_ui.bind().get_move_buffer();
+
         self.save_manager
             .bind_mut()
-            .update_save_game(Some(SaveGame::new(new_game_seed)));
+            .update_save_game(Some(new_game));
 
         if let Some(ref mut scene_tree) = self.base().get_tree() {
             scene_tree.change_scene_to_file("res://scenes/game/game.tscn");
@@ -76,6 +131,8 @@ impl INode for MainMenuManager {
 
             main_menu_ui: OnEditor::default(),
 
+            replay_names: vec![],
+
             base,
         }
     }
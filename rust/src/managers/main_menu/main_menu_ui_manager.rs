@@ -1,9 +1,10 @@
-use godot::classes::{Button, CanvasItem, INode, Node, SpinBox};
+use godot::classes::{Button, CanvasItem, CheckBox, INode, ItemList, Node, SpinBox};
 use godot::prelude::*;
 
 pub enum MenuUIView {
     Home,
     GameSetup,
+    Replays,
 }
 
 #[derive(GodotClass)]
@@ -13,6 +14,8 @@ pub struct MainMenuUIManager {
     start_menu_root: OnEditor<Gd<CanvasItem>>,
     #[export]
     game_setup_root: OnEditor<Gd<CanvasItem>>,
+    #[export]
+    replays_root: OnEditor<Gd<CanvasItem>>,
 
     #[export]
     resume_button: OnEditor<Gd<Button>>,
@@ -20,6 +23,12 @@ pub struct MainMenuUIManager {
     #[export]
     seed_box: OnEditor<Gd<SpinBox>>,
 
+    #[export]
+    move_buffer_box: OnEditor<Gd<CheckBox>>,
+
+    #[export]
+    replay_list: OnEditor<Gd<ItemList>>,
+
     base: Base<Node>,
 }
 
@@ -30,11 +39,22 @@ impl MainMenuUIManager {
                 self.start_menu_root.set_visible(true);
 
                 self.game_setup_root.set_visible(false);
+
+                self.replays_root.set_visible(false);
             }
             MenuUIView::GameSetup => {
                 self.start_menu_root.set_visible(false);
 
                 self.game_setup_root.set_visible(true);
+
+                self.replays_root.set_visible(false);
+            }
+            MenuUIView::Replays => {
+                self.start_menu_root.set_visible(false);
+
+                self.game_setup_root.set_visible(false);
+
+                self.replays_root.set_visible(true);
             }
         }
     }
@@ -50,6 +70,26 @@ impl MainMenuUIManager {
     pub fn set_seed(&mut self, new_seed: i64) {
         self.seed_box.set_value(new_seed as f64);
     }
+
+    pub fn get_move_buffer(&self) -> bool {
+        self.move_buffer_box.is_pressed()
+    }
+
+    pub fn set_replays(&mut self, replay_names: &[String]) {
+        self.replay_list.clear();
+
+        for replay_name in replay_names {
+            self.replay_list.add_item(replay_name);
+        }
+    }
+
+    pub fn get_selected_replay(&mut self) -> Option<usize> {
+        self.replay_list
+            .get_selected_items()
+            .as_slice()
+            .first()
+            .map(|index| *index as usize)
+    }
 }
 
 #[godot_api]
@@ -58,11 +98,16 @@ impl INode for MainMenuUIManager {
         Self {
             start_menu_root: OnEditor::default(),
             game_setup_root: OnEditor::default(),
+            replays_root: OnEditor::default(),
 
             resume_button: OnEditor::default(),
 
             seed_box: OnEditor::default(),
 
+            move_buffer_box: OnEditor::default(),
+
+            replay_list: OnEditor::default(),
+
             base,
         }
     }
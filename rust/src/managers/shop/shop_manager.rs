@@ -1,6 +1,8 @@
+use crate::managers::game::floor_manager::FloorManager;
 use crate::managers::save_manager::SaveManager;
 use crate::managers::shop::shop_ui_manager::ShopUIManager;
 use crate::types::save_game::SaveGame;
+use crate::types::seed::SeedStream;
 use crate::types::upgrades::UpgradeType;
 use godot::classes::{INode, Node};
 use godot::prelude::*;
@@ -75,6 +77,8 @@ impl ShopManager {
             self.save_manager
                 .bind_mut()
                 .update_save_game(Some(self.current_game.clone()));
+
+            FloorManager::prepare_level(self.current_game.clone());
         }
     }
 
@@ -111,16 +115,19 @@ impl ShopManager {
             self.save_manager
                 .bind_mut()
                 .update_save_game(Some(self.current_game.clone()));
+
+            // upgrades change the par time of the floor being prepared
+            FloorManager::prepare_level(self.current_game.clone());
         }
     }
 
     pub fn populate_shop(&mut self) {
-        let mut rng = SmallRng::from_seed(self.current_game.get_rng_seed());
-
-        // fixes rng somehow????
-        for _ in 0..3 {
-            let _ = UpgradeType::generate_random(&mut rng);
-        }
+        let mut rng = self
+            .current_game
+            .get_floor_seed()
+            .stream(SeedStream::ShopOffers)
+            .child(self.current_game.mod_shop_page as u64)
+            .rng();
 
         self.upgrades = [
             (UpgradeType::generate_random(&mut rng), false),
@@ -180,6 +187,11 @@ impl INode for ShopManager {
 
         self.populate_shop();
 
+        // without a save game the scene is already on its way back to the main menu
+        if self.save_manager.bind().save_game.is_some() {
+            FloorManager::prepare_level(self.current_game.clone());
+        }
+
         self.shop_ui
             .bind_mut()
             .set_reroll(self.reroll_cost, self.reroll_cost < self.current_game.money);
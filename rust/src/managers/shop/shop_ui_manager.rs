@@ -29,12 +29,25 @@ impl ShopUIManager {
         self.current_money
             .set_text(&format!("wallet: ${}", game.money));
         self.current_upgrades
-            .set_text(&format!("preview time: {:.1}s\ntop speed: {:.1}m/s\nacceleration: {:.1}m/s^2\nview distance: {:.1}m\nmass: {:.1}kg",
+            .set_text(&format!("preview time: {:.1}s\ntop speed: {:.1}m/s\nacceleration: {:.1}m/s^2\nview distance: {:.1}m\nmass: {:.1}kg\nturn buffer: {}\nredirector: {}\nextra eyes: {}",
             	game.player_properties.warmup_time,
              	game.player_properties.max_speed,
               	game.player_properties.active_acceleration,
                	game.player_properties.view_distance,
-                game.player_properties.stopping_mass
+                game.player_properties.stopping_mass,
+                if game.player_properties.has_move_buffer {
+                    format!("{:.2}s", game.player_properties.move_buffer_window)
+                } else {
+                    "none".to_owned()
+                },
+                match game.player_properties.momentum_redirector {
+                    Some(ref redirector) => format!("{:.1}s cooldown", redirector.cooldown),
+                    None => "none".to_owned(),
+                },
+                game.player_properties
+                    .extra_eye
+                    .as_ref()
+                    .map_or(0, |extra_eye| extra_eye.count)
             ));
     }
 
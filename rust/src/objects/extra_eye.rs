@@ -0,0 +1,136 @@
+use crate::objects::map::ROOM_GRID_BASIS;
+use crate::types::floor_layout::FloorLayout;
+use crate::types::simulation::SIMULATION_TICK_DELTA;
+use godot::classes::{INode3D, Node3D};
+use godot::prelude::*;
+
+// how long an eye stays at the end of its corridor before it is cleaned up
+const LINGER_TIME: f64 = 0.5;
+
+#[derive(GodotClass)]
+#[class(base=Node3D)]
+pub struct ExtraEye {
+    floor_layout: FloorLayout,
+
+    direction: (i64, i64),
+    speed: f64,
+
+    target_position: (i64, i64),
+
+    is_stopped: bool,
+    linger_timer: f64,
+
+    base: Base<Node3D>,
+}
+
+impl ExtraEye {
+    pub fn launch(
+        &mut self,
+        floor_layout: FloorLayout,
+        from: (i64, i64),
+        direction: (i64, i64),
+        speed: f64,
+    ) {
+        self.floor_layout = floor_layout;
+
+        self.direction = direction;
+        self.speed = speed;
+
+        self.target_position = (from.0 + direction.0, from.1 + direction.1);
+
+        self.is_stopped = false;
+        self.linger_timer = LINGER_TIME;
+
+        self.base_mut().set_position(Vector3::new(
+            from.0 as f32 * ROOM_GRID_BASIS,
+            from.1 as f32 * ROOM_GRID_BASIS,
+            0.0,
+        ));
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.is_stopped && self.linger_timer <= 0.0
+    }
+
+    pub fn simulate_tick(&mut self) {
+        let delta = SIMULATION_TICK_DELTA;
+
+        if self.is_stopped {
+            self.linger_timer -= delta;
+
+            return;
+        }
+
+        let speed_vector = Vector3::new(
+            (self.speed as f32 * self.direction.0 as f32) * delta as f32,
+            (self.speed as f32 * self.direction.1 as f32) * delta as f32,
+            0.0,
+        );
+
+        let position = self.base().get_position() + speed_vector;
+
+        self.base_mut().set_position(position);
+
+        if !self.has_passed_target(position) {
+            return;
+        }
+
+        let next_position = (
+            self.target_position.0 + self.direction.0,
+            self.target_position.1 + self.direction.1,
+        );
+
+        // eyes only see down a straight corridor, they stop at the first bend or wall
+        if self
+            .floor_layout
+            .has_corridor(self.target_position, next_position)
+        {
+            self.target_position = next_position;
+        } else {
+            let target_position = self.target_position;
+
+            self.base_mut().set_position(Vector3::new(
+                target_position.0 as f32 * ROOM_GRID_BASIS,
+                target_position.1 as f32 * ROOM_GRID_BASIS,
+                0.0,
+            ));
+
+            self.is_stopped = true;
+        }
+    }
+
+    fn has_passed_target(&self, position: Vector3) -> bool {
+        let target = Vector3::new(
+            self.target_position.0 as f32 * ROOM_GRID_BASIS,
+            self.target_position.1 as f32 * ROOM_GRID_BASIS,
+            0.0,
+        );
+
+        match self.direction {
+            (0, 1) => position.y >= target.y,
+            (0, -1) => position.y <= target.y,
+            (-1, 0) => position.x <= target.x,
+            (1, 0) => position.x >= target.x,
+            _ => true,
+        }
+    }
+}
+
+#[godot_api]
+impl INode3D for ExtraEye {
+    fn init(base: Base<Node3D>) -> Self {
+        Self {
+            floor_layout: FloorLayout::default(),
+
+            direction: (0, 0),
+            speed: 0.0,
+
+            target_position: (0, 0),
+
+            is_stopped: true,
+            linger_timer: 0.0,
+
+            base,
+        }
+    }
+}
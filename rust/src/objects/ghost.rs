@@ -0,0 +1,76 @@
+use crate::types::ghost::GhostTrace;
+use godot::classes::{AnimatedSprite3D, INode3D, Node3D};
+use godot::prelude::*;
+
+#[derive(GodotClass)]
+#[class(base=Node3D)]
+pub struct Ghost {
+    #[export]
+    sprite: OnEditor<Gd<AnimatedSprite3D>>,
+
+    trace: Option<GhostTrace>,
+
+    base: Base<Node3D>,
+}
+
+impl Ghost {
+    pub fn set_trace(&mut self, trace: Option<GhostTrace>) {
+        self.trace = trace;
+
+        let has_trace = self.trace.is_some();
+
+        self.base_mut().set_visible(has_trace);
+
+        self.set_time(0.0);
+    }
+
+    pub fn set_time(&mut self, time: f64) {
+        let Some(ref trace) = self.trace else {
+            return;
+        };
+
+        let Some(position) = trace.get_position_at(time) else {
+            return;
+        };
+
+        let position = Vector3::new(position.0 as f32, position.1 as f32, 0.0);
+
+        let movement = position - self.base().get_position();
+
+        // face the way the ghost is going, same as the player's sprite
+        if movement.length() > 0.001 {
+            let rotation = if movement.x.abs() > movement.y.abs() {
+                if movement.x < 0.0 { 180.0 } else { 0.0 }
+            } else if movement.y > 0.0 {
+                90.0
+            } else {
+                270.0
+            };
+
+            self.sprite
+                .set_rotation_degrees(Vector3::new(0.0, 0.0, rotation));
+        }
+
+        self.base_mut().set_position(position);
+    }
+}
+
+#[godot_api]
+impl INode3D for Ghost {
+    fn init(base: Base<Node3D>) -> Self {
+        Self {
+            sprite: OnEditor::default(),
+
+            trace: None,
+
+            base,
+        }
+    }
+
+    fn ready(&mut self) {
+        self.base_mut().set_visible(false);
+
+        self.sprite.set_animation("run");
+        self.sprite.play();
+    }
+}
@@ -1,6 +1,10 @@
-use crate::managers::game::floor_manager::FloorLayout;
+use crate::objects::extra_eye::ExtraEye;
+use crate::objects::map::ROOM_GRID_BASIS;
 use crate::objects::map::room::Room;
 use crate::objects::player::Player;
+use crate::types::completion_rules::SectionWeights;
+use crate::types::floor_layout::FloorLayout;
+use crate::types::player_properties::PlayerProperties;
 use crate::types::rooms::RoomType;
 use godot::classes::{INode3D, Node3D};
 use godot::prelude::*;
@@ -12,59 +16,286 @@ pub struct Floor {
     #[export]
     player: OnEditor<Gd<Player>>,
 
+    // rooms by grid position, so whatever a viewer can see is looked up directly
     floor_objects: HashMap<(i64, i64), Gd<Room>>,
 
+    floor_layout: FloorLayout,
+
+    view_distance: f64,
+
+    // every room's seen_progress added up, kept in step as rooms are revealed
+    seen_total: f64,
+    revealed_room_count: usize,
+
+    collected_coins: i64,
+
+    extra_eyes: Vec<Gd<ExtraEye>>,
+
     normal_room_scene: Gd<PackedScene>,
+    treasure_room_scene: Gd<PackedScene>,
+    hazard_room_scene: Gd<PackedScene>,
+    teleporter_room_scene: Gd<PackedScene>,
+
+    extra_eye_scene: Gd<PackedScene>,
 
     base: Base<Node3D>,
 }
 
+// the room signals again, for anything that wants to hear about every room on the floor
+#[godot_api]
+impl Floor {
+    #[signal]
+    pub fn room_section_revealed(position: Vector2i, section: StringName);
+
+    #[signal]
+    pub fn room_fully_revealed(position: Vector2i);
+
+    #[signal]
+    pub fn floor_completion_changed(progress: f64);
+}
+
 impl Floor {
-    pub fn load_floor(&mut self, layout: FloorLayout) {
+    pub fn load_floor(
+        &mut self,
+        layout: FloorLayout,
+        player_properties: &PlayerProperties,
+        section_weights: &SectionWeights,
+    ) {
+        self.view_distance = player_properties.view_distance;
+
+        for extra_eye in self.extra_eyes.drain(..) {
+            extra_eye.free();
+        }
+
         for (_, node) in self.floor_objects.drain() {
             node.free();
         }
 
-        for (position, room_type) in layout.iter() {
-            match room_type {
-                RoomType::Normal => {
-                    let mut room = self.normal_room_scene.instantiate_as::<Room>();
+        for (position, room_type) in layout.rooms() {
+            let mut room = match room_type {
+                RoomType::Normal => self.normal_room_scene.instantiate_as::<Room>(),
+                RoomType::Treasure(coins) => {
+                    let mut room = self.treasure_room_scene.instantiate_as::<Room>();
 
-                    room.bind_mut().player = Some(self.player.clone());
+                    room.bind_mut().set_coins(*coins);
 
-                    self.base_mut().add_child(&room);
+                    room
+                }
+                RoomType::Hazard(hazard) => {
+                    let mut room = self.hazard_room_scene.instantiate_as::<Room>();
 
-                    room.bind_mut().place(*position);
+                    room.bind_mut().set_hazard(hazard.clone());
 
-                    let has_room_left = layout.contains_key(&(position.0 - 1, position.1));
-                    let has_room_right = layout.contains_key(&(position.0 + 1, position.1));
-                    let has_room_top = layout.contains_key(&(position.0, position.1 - 1));
-                    let has_room_bottom = layout.contains_key(&(position.0, position.1 + 1));
-                    room.bind_mut().set_corridors(
-                        has_room_left,
-                        has_room_right,
-                        has_room_top,
-                        has_room_bottom,
-                    );
+                    room
+                }
+                RoomType::Teleporter(_) => self.teleporter_room_scene.instantiate_as::<Room>(),
+            };
 
-                    room.bind_mut().reset();
+            self.base_mut().add_child(&room);
 
-                    self.floor_objects.insert(*position, room);
-                }
-            }
+            room.bind_mut().place(*position);
+
+            let has_corridor_left = layout.has_corridor(*position, (position.0 - 1, position.1));
+            let has_corridor_right = layout.has_corridor(*position, (position.0 + 1, position.1));
+            let has_corridor_top = layout.has_corridor(*position, (position.0, position.1 - 1));
+            let has_corridor_bottom = layout.has_corridor(*position, (position.0, position.1 + 1));
+            room.bind_mut().set_corridors(
+                has_corridor_left,
+                has_corridor_right,
+                has_corridor_top,
+                has_corridor_bottom,
+            );
+
+            room.bind_mut().set_section_weights(section_weights.clone());
+            room.bind_mut().reset();
+
+            room.signals()
+                .room_section_revealed()
+                .connect_other(self, Self::on_room_section_revealed);
+            room.signals()
+                .room_fully_revealed()
+                .connect_other(self, Self::on_room_fully_revealed);
+
+            self.floor_objects.insert(*position, room);
+        }
+
+        self.floor_layout = layout;
+
+        self.seen_total = 0.0;
+        self.revealed_room_count = 0;
+
+        self.collected_coins = 0;
+
+        self.emit_floor_completion_changed(0.0);
+    }
+
+    // deferred like the room signals, the floor is set up and simulated while the game is bound
+    fn emit_floor_completion_changed(&mut self, progress: f64) {
+        self.base_mut().call_deferred(
+            "emit_signal",
+            &[
+                "floor_completion_changed".to_variant(),
+                progress.to_variant(),
+            ],
+        );
+    }
+
+    pub fn get_collected_coins(&self) -> i64 {
+        self.collected_coins
+    }
+
+    pub fn get_floor_layout(&self) -> &FloorLayout {
+        &self.floor_layout
+    }
+
+    // the room's seen_progress and whether every part of it has been seen
+    pub fn get_room_seen_progress(&self, position: &(i64, i64)) -> Option<(f64, bool)> {
+        self.floor_objects.get(position).map(|room| {
+            let room = room.bind();
+
+            (room.seen_progress, room.is_completely_revealed())
+        })
+    }
+
+    // rooms emit deferred, so these already run after the tick
+    fn on_room_section_revealed(&mut self, position: Vector2i, section: StringName) {
+        self.signals()
+            .room_section_revealed()
+            .emit(position, &section);
+    }
+
+    fn on_room_fully_revealed(&mut self, position: Vector2i) {
+        self.signals().room_fully_revealed().emit(position);
+    }
+
+    fn on_player_teleported(&mut self, from: Vector2i, _to: Vector2i) {
+        // the player is moved away before they can be seen standing in the entrance
+        self.reveal_room((from.x as i64, from.y as i64), |room| room.reveal());
+    }
+
+    fn on_extra_eye_deployed(&mut self, from: Vector2i, direction: Vector2i, speed: f64) {
+        let mut extra_eye = self.extra_eye_scene.instantiate_as::<ExtraEye>();
+
+        self.base_mut().add_child(&extra_eye);
+
+        extra_eye.bind_mut().launch(
+            self.floor_layout.clone(),
+            (from.x as i64, from.y as i64),
+            (direction.x as i64, direction.y as i64),
+            speed,
+        );
+
+        self.extra_eyes.push(extra_eye);
+    }
+
+    // eyes move before anything looks from them, same order every tick
+    pub fn simulate_tick(&mut self) {
+        for extra_eye in self.extra_eyes.iter_mut() {
+            extra_eye.bind_mut().simulate_tick();
+        }
+
+        let player_position = self.player.get_position();
+
+        if let Some(room) = self
+            .floor_objects
+            .get_mut(&Self::get_grid_position(player_position))
+        {
+            self.collected_coins += room.bind_mut().collect_coins(player_position);
+        }
+
+        let viewer_positions: Vec<Vector3> = std::iter::once(player_position)
+            .chain(
+                self.extra_eyes
+                    .iter()
+                    .map(|extra_eye| extra_eye.get_position()),
+            )
+            .collect();
+
+        for viewer_position in viewer_positions {
+            self.reveal_around(viewer_position);
+        }
+
+        let (finished, active): (Vec<_>, Vec<_>) = self
+            .extra_eyes
+            .drain(..)
+            .partition(|extra_eye| extra_eye.bind().is_finished());
+
+        self.extra_eyes = active;
+
+        for extra_eye in finished {
+            extra_eye.free();
+        }
+    }
+
+    fn get_grid_position(position: Vector3) -> (i64, i64) {
+        (
+            (position.x / ROOM_GRID_BASIS).round() as i64,
+            (position.y / ROOM_GRID_BASIS).round() as i64,
+        )
+    }
+
+    fn get_view_range(&self) -> f32 {
+        self.view_distance as f32 + (ROOM_GRID_BASIS / 2.0)
+    }
+
+    // only rooms the viewer has a line of sight to down open corridors
+    fn reveal_around(&mut self, viewer_position: Vector3) {
+        let visible_rooms = self.floor_layout.get_visible_rooms(
+            (viewer_position.x as f64, viewer_position.y as f64),
+            self.get_view_range() as f64,
+        );
+
+        for position in visible_rooms {
+            self.reveal_room(position, |room| room.reveal_from(viewer_position));
+        }
+    }
+
+    // runs a reveal on one room and folds whatever it uncovered into the running totals, the room
+    // tells about its own sections
+    fn reveal_room(&mut self, position: (i64, i64), reveal: impl FnOnce(&mut Room)) {
+        let Some(room) = self.floor_objects.get_mut(&position) else {
+            return;
+        };
+
+        let mut room = room.bind_mut();
+
+        if room.is_completely_revealed() {
+            return;
+        }
+
+        let last_seen_progress = room.seen_progress;
+
+        reveal(&mut room);
+
+        let seen_progress = room.seen_progress;
+        let is_completely_revealed = room.is_completely_revealed();
+
+        drop(room);
+
+        if is_completely_revealed {
+            self.revealed_room_count += 1;
+        }
+
+        if seen_progress != last_seen_progress {
+            self.seen_total += seen_progress - last_seen_progress;
+
+            let completion_progress = self.get_completion_progress();
+
+            self.emit_floor_completion_changed(completion_progress);
         }
     }
 
     pub fn get_completion_progress(&self) -> f64 {
-        let mut progress_accumulator = 0.0;
-        let mut number_objects = 0.0;
+        if self.floor_objects.is_empty() {
+            return 0.0;
+        }
 
-        for (_, room) in self.floor_objects.iter() {
-            progress_accumulator += room.bind().seen_progress;
-            number_objects += 1.0;
+        // counted separately so rounding in the running total can't hold back the finish
+        if self.revealed_room_count == self.floor_objects.len() {
+            return 1.0;
         }
 
-        progress_accumulator / number_objects
+        (self.seen_total / self.floor_objects.len() as f64).min(1.0)
     }
 }
 
@@ -76,9 +307,37 @@ impl INode3D for Floor {
 
             floor_objects: HashMap::new(),
 
+            floor_layout: FloorLayout::new(),
+
+            view_distance: PlayerProperties::default().view_distance,
+
+            seen_total: 0.0,
+            revealed_room_count: 0,
+
+            collected_coins: 0,
+
+            extra_eyes: vec![],
+
             normal_room_scene: load::<PackedScene>("res://objects/room/room.tscn"),
+            treasure_room_scene: load::<PackedScene>("res://objects/room/treasure_room.tscn"),
+            hazard_room_scene: load::<PackedScene>("res://objects/room/hazard_room.tscn"),
+            teleporter_room_scene: load::<PackedScene>("res://objects/room/teleporter_room.tscn"),
+
+            extra_eye_scene: load::<PackedScene>("res://objects/extra_eye/extra_eye.tscn"),
 
             base,
         }
     }
+
+    fn ready(&mut self) {
+        self.player
+            .signals()
+            .teleported()
+            .connect_other(self, Self::on_player_teleported);
+
+        self.player
+            .signals()
+            .extra_eye_deployed()
+            .connect_other(self, Self::on_extra_eye_deployed);
+    }
 }
@@ -0,0 +1,187 @@
+use crate::objects::map::ROOM_GRID_BASIS;
+use crate::objects::map::floor::Floor;
+use crate::objects::player::Player;
+use crate::types::movement::RIGHT_DIRECTION;
+use godot::classes::{Control, IControl};
+use godot::prelude::*;
+
+// share of a grid cell a room and a corridor take up on the map
+const ROOM_FILL: f32 = 0.55;
+const CORRIDOR_WIDTH: f32 = 0.15;
+
+const HATCH_SPACING: f32 = 4.0;
+
+const PLAYER_RADIUS: f32 = 3.0;
+
+const BACKGROUND_COLOR: Color = Color::from_rgba(0.0, 0.0, 0.0, 0.5);
+const UNSEEN_COLOR: Color = Color::from_rgba(1.0, 1.0, 1.0, 0.15);
+const SEEN_COLOR: Color = Color::from_rgba(1.0, 1.0, 1.0, 0.85);
+const PLAYER_COLOR: Color = Color::from_rgba(1.0, 0.8, 0.2, 1.0);
+
+// a small top down map of the floor kept centered on the player, for when the map view is hidden
+#[derive(GodotClass)]
+#[class(base=Control)]
+pub struct Minimap {
+    #[export]
+    floor: OnEditor<Gd<Floor>>,
+    #[export]
+    player: OnEditor<Gd<Player>>,
+
+    // pixels one room takes up on the map
+    #[export]
+    room_size: f32,
+
+    // the player keeps facing the last way they went while standing still
+    facing: (i64, i64),
+
+    base: Base<Control>,
+}
+
+impl Minimap {
+    fn to_map_position(
+        &self,
+        center: Vector2,
+        position: (f64, f64),
+        origin: (f64, f64),
+    ) -> Vector2 {
+        let scale = self.room_size / ROOM_GRID_BASIS;
+
+        // world up is screen up, the other way round from control coordinates
+        center
+            + Vector2::new(
+                (position.0 - origin.0) as f32,
+                -(position.1 - origin.1) as f32,
+            ) * scale
+    }
+
+    fn draw_hatched_rect(&mut self, rect: Rect2, color: Color) {
+        let size = rect.size.x;
+        let mut offset = HATCH_SPACING;
+
+        // lines where x + y is constant, cut off at the edges of the rect
+        while offset < size * 2.0 {
+            let from = rect.position + Vector2::new(offset.min(size), (offset - size).max(0.0));
+            let to = rect.position + Vector2::new((offset - size).max(0.0), offset.min(size));
+
+            self.base_mut().draw_line(from, to, color);
+
+            offset += HATCH_SPACING;
+        }
+
+        self.base_mut()
+            .draw_rect_ex(rect, color)
+            .filled(false)
+            .width(1.0)
+            .done();
+    }
+
+    fn is_seen(progress: Option<(f64, bool)>) -> bool {
+        progress.is_some_and(|(progress, _)| progress > 0.0)
+    }
+}
+
+#[godot_api]
+impl IControl for Minimap {
+    fn init(base: Base<Control>) -> Self {
+        Self {
+            floor: OnEditor::default(),
+            player: OnEditor::default(),
+
+            room_size: 24.0,
+
+            facing: RIGHT_DIRECTION,
+
+            base,
+        }
+    }
+
+    fn process(&mut self, _delta: f64) {
+        if self.base().is_visible_in_tree() {
+            self.base_mut().queue_redraw();
+        }
+    }
+
+    fn draw(&mut self) {
+        let state = self.player.bind().get_movement_state();
+
+        if state.direction != (0, 0) {
+            self.facing = state.direction;
+        }
+
+        let size = self.base().get_size();
+        let center = size / 2.0;
+
+        self.base_mut()
+            .draw_rect(Rect2::new(Vector2::ZERO, size), BACKGROUND_COLOR);
+
+        let floor = self.floor.clone();
+        let floor = floor.bind();
+        let layout = floor.get_floor_layout();
+
+        let room_extent = self.room_size * ROOM_FILL;
+        let corridor_width = self.room_size * CORRIDOR_WIDTH;
+
+        let world_position = |(x, y): (i64, i64)| {
+            (
+                x as f64 * ROOM_GRID_BASIS as f64,
+                y as f64 * ROOM_GRID_BASIS as f64,
+            )
+        };
+
+        // corridors first so the rooms are drawn over their ends
+        for (position, _) in layout.rooms() {
+            for next in [(position.0 + 1, position.1), (position.0, position.1 + 1)] {
+                if !layout.has_corridor(*position, next) {
+                    continue;
+                }
+
+                let seen = Self::is_seen(floor.get_room_seen_progress(position))
+                    || Self::is_seen(floor.get_room_seen_progress(&next));
+
+                let from = self.to_map_position(center, world_position(*position), state.position);
+                let to = self.to_map_position(center, world_position(next), state.position);
+
+                self.base_mut()
+                    .draw_line_ex(from, to, if seen { SEEN_COLOR } else { UNSEEN_COLOR })
+                    .width(corridor_width)
+                    .done();
+            }
+        }
+
+        for (position, _) in layout.rooms() {
+            let progress = floor.get_room_seen_progress(position);
+
+            let room_center =
+                self.to_map_position(center, world_position(*position), state.position);
+            let rect = Rect2::new(
+                room_center - Vector2::new(room_extent, room_extent) / 2.0,
+                Vector2::new(room_extent, room_extent),
+            );
+
+            match progress {
+                Some((_, true)) => self.base_mut().draw_rect(rect, SEEN_COLOR),
+                _ if Self::is_seen(progress) => {
+                    self.base_mut().draw_rect(rect, UNSEEN_COLOR);
+                    self.draw_hatched_rect(rect, SEEN_COLOR);
+                }
+                _ => self.base_mut().draw_rect(rect, UNSEEN_COLOR),
+            }
+        }
+
+        drop(floor);
+
+        let facing = Vector2::new(self.facing.0 as f32, -self.facing.1 as f32);
+        let side = Vector2::new(-facing.y, facing.x);
+
+        self.base_mut()
+            .draw_circle(center, PLAYER_RADIUS, PLAYER_COLOR);
+        self.base_mut().draw_colored_polygon(
+            &PackedVector2Array::from(&[
+                center + facing * PLAYER_RADIUS * 3.0,
+                center + side * PLAYER_RADIUS,
+                center - side * PLAYER_RADIUS,
+            ]),
+            PLAYER_COLOR,
+        );
+    }
+}
@@ -1,4 +1,5 @@
 pub mod floor;
+pub mod minimap;
 pub mod room;
 
-pub const ROOM_GRID_BASIS: f32 = 8.0;
+pub use crate::types::floor_layout::ROOM_GRID_BASIS;
@@ -1,6 +1,9 @@
 use crate::objects::map::ROOM_GRID_BASIS;
-use crate::objects::player::Player;
-use crate::types::save_game::SaveGame;
+use crate::types::completion_rules::SectionWeights;
+use crate::types::room_sections::{
+    get_newly_seen_sections, get_sections_seen_from, merge_sections,
+};
+use crate::types::rooms::HazardType;
 use godot::classes::{INode3D, MeshInstance3D, Node3D};
 use godot::prelude::*;
 
@@ -13,9 +16,6 @@ const NO_CORRIDOR_VERTICAL: f32 = -0.1;
 #[derive(GodotClass)]
 #[class(base=Node3D)]
 pub struct Room {
-    #[export]
-    pub player: Option<Gd<Player>>,
-
     #[export]
     center_fog: OnEditor<Gd<MeshInstance3D>>,
 
@@ -37,6 +37,14 @@ pub struct Room {
     #[export]
     bottom_corridor: OnEditor<Gd<MeshInstance3D>>,
 
+    #[export]
+    treasure: Option<Gd<Node3D>>,
+
+    #[export]
+    spikes_marker: Option<Gd<Node3D>>,
+    #[export]
+    ice_marker: Option<Gd<Node3D>>,
+
     room_completely_revealed: bool,
 
     seen_horizontal_corridors: bool,
@@ -49,15 +57,31 @@ pub struct Room {
     seen_bottom_left: bool,
     seen_bottom_right: bool,
 
-    current_game: SaveGame,
+    grid_position: (i64, i64),
+
+    coins: i64,
+    coins_collected: bool,
+
+    section_weights: SectionWeights,
 
     pub seen_progress: f64,
 
     base: Base<Node3D>,
 }
 
+#[godot_api]
+impl Room {
+    #[signal]
+    pub fn room_section_revealed(position: Vector2i, section: StringName);
+
+    #[signal]
+    pub fn room_fully_revealed(position: Vector2i);
+}
+
 impl Room {
     pub fn place(&mut self, (x, y): (i64, i64)) {
+        self.grid_position = (x, y);
+
         self.base_mut().set_position(Vector3 {
             x: x as f32 * ROOM_GRID_BASIS,
             y: y as f32 * ROOM_GRID_BASIS,
@@ -79,6 +103,51 @@ impl Room {
         self.seen_bottom_right = false;
 
         self.seen_progress = 0.0;
+
+        self.coins_collected = false;
+
+        self.update_treasure();
+    }
+
+    pub fn set_coins(&mut self, coins: i64) {
+        self.coins = coins;
+    }
+
+    pub fn reveal(&mut self) {
+        let last_seen_sections = self.get_seen_sections();
+
+        self.seen_horizontal_corridors = true;
+        self.seen_vertical_corridors = true;
+
+        self.seen_center = true;
+
+        self.seen_top_left = true;
+        self.seen_top_right = true;
+        self.seen_bottom_left = true;
+        self.seen_bottom_right = true;
+
+        self.room_completely_revealed = true;
+        self.seen_progress = 1.0;
+
+        self.on_sections_revealed(last_seen_sections);
+    }
+
+    pub fn set_hazard(&mut self, hazard: HazardType) {
+        if let Some(ref mut spikes_marker) = self.spikes_marker {
+            spikes_marker.set_visible(hazard == HazardType::Spikes);
+        }
+
+        if let Some(ref mut ice_marker) = self.ice_marker {
+            ice_marker.set_visible(hazard == HazardType::Ice);
+        }
+    }
+
+    fn update_treasure(&mut self) {
+        let is_visible = self.seen_center && !self.coins_collected;
+
+        if let Some(ref mut treasure) = self.treasure {
+            treasure.set_visible(is_visible);
+        }
     }
 
     pub fn set_corridors(&mut self, left: bool, right: bool, top: bool, bottom: bool) {
@@ -136,8 +205,141 @@ impl Room {
         ));
     }
 
-    pub fn set_current_game(&mut self, current_game: SaveGame) {
-        self.current_game = current_game;
+    pub fn is_completely_revealed(&self) -> bool {
+        self.room_completely_revealed
+    }
+
+    // the floor only asks rooms within view distance of a viewer
+    pub fn reveal_from(&mut self, viewer_position: Vector3) {
+        let last_seen_sections = self.get_seen_sections();
+
+        let room_position = self.base().get_position();
+
+        let seen_sections = merge_sections(
+            last_seen_sections,
+            get_sections_seen_from(
+                (room_position.x as f64, room_position.y as f64),
+                (viewer_position.x as f64, viewer_position.y as f64),
+            ),
+        );
+
+        self.set_seen_sections(seen_sections);
+
+        self.update_seen_progress();
+
+        self.on_sections_revealed(last_seen_sections);
+    }
+
+    // the coins picked up just now, if the player is standing on them
+    pub fn collect_coins(&mut self, player_position: Vector3) -> i64 {
+        let room_position = self.base().get_position();
+
+        if self.coins > 0
+            && !self.coins_collected
+            && (player_position.x - room_position.x).abs() < 1.5
+            && (player_position.y - room_position.y).abs() < 1.5
+        {
+            self.coins_collected = true;
+
+            self.update_treasure();
+
+            return self.coins;
+        }
+
+        0
+    }
+
+    pub fn set_section_weights(&mut self, section_weights: SectionWeights) {
+        self.section_weights = section_weights;
+    }
+
+    // updates the fog and tells anyone listening about sections that were not seen before, the
+    // signals go out deferred so listeners run after the tick instead of while it holds the game
+    fn on_sections_revealed(&mut self, last_seen_sections: [bool; 7]) {
+        let seen_sections = self.get_seen_sections();
+
+        if seen_sections == last_seen_sections {
+            return;
+        }
+
+        self.update_fog();
+
+        let position = Vector2i::new(self.grid_position.0 as i32, self.grid_position.1 as i32);
+
+        for section in get_newly_seen_sections(last_seen_sections, seen_sections) {
+            self.base_mut().call_deferred(
+                "emit_signal",
+                &[
+                    "room_section_revealed".to_variant(),
+                    position.to_variant(),
+                    StringName::from(section).to_variant(),
+                ],
+            );
+        }
+
+        if self.room_completely_revealed {
+            self.base_mut().call_deferred(
+                "emit_signal",
+                &["room_fully_revealed".to_variant(), position.to_variant()],
+            );
+        }
+    }
+
+    pub fn get_seen_sections(&self) -> [bool; 7] {
+        [
+            self.seen_horizontal_corridors,
+            self.seen_vertical_corridors,
+            self.seen_center,
+            self.seen_top_left,
+            self.seen_top_right,
+            self.seen_bottom_left,
+            self.seen_bottom_right,
+        ]
+    }
+
+    fn set_seen_sections(&mut self, seen_sections: [bool; 7]) {
+        [
+            self.seen_horizontal_corridors,
+            self.seen_vertical_corridors,
+            self.seen_center,
+            self.seen_top_left,
+            self.seen_top_right,
+            self.seen_bottom_left,
+            self.seen_bottom_right,
+        ] = seen_sections;
+    }
+
+    fn update_seen_progress(&mut self) {
+        let weights = &self.section_weights;
+
+        let corners = [
+            self.seen_top_left,
+            self.seen_top_right,
+            self.seen_bottom_left,
+            self.seen_bottom_right,
+        ]
+        .iter()
+        .filter(|seen| **seen)
+        .count();
+
+        self.seen_progress = if self.seen_horizontal_corridors {
+            weights.horizontal_corridors
+        } else {
+            0.0
+        } + if self.seen_vertical_corridors {
+            weights.vertical_corridors
+        } else {
+            0.0
+        } + if self.seen_center {
+            weights.center
+        } else {
+            0.0
+        } + weights.corner * corners as f64;
+
+        if self.get_seen_sections().iter().all(|seen| *seen) {
+            self.room_completely_revealed = true;
+            self.seen_progress = 1.0;
+        }
     }
 
     pub fn update_fog(&mut self) {
@@ -195,6 +397,8 @@ impl Room {
             "seen",
             &Variant::from(if self.seen_bottom_right { 1.0 } else { 0.0 }),
         );
+
+        self.update_treasure();
     }
 }
 
@@ -202,8 +406,6 @@ impl Room {
 impl INode3D for Room {
     fn init(base: Base<Node3D>) -> Self {
         Self {
-            player: None,
-
             center_fog: OnEditor::default(),
 
             top_left_fog: OnEditor::default(),
@@ -216,6 +418,11 @@ impl INode3D for Room {
             top_corridor: OnEditor::default(),
             bottom_corridor: OnEditor::default(),
 
+            treasure: None,
+
+            spikes_marker: None,
+            ice_marker: None,
+
             room_completely_revealed: false,
 
             seen_horizontal_corridors: false,
@@ -228,7 +435,12 @@ impl INode3D for Room {
             seen_bottom_left: false,
             seen_bottom_right: false,
 
-            current_game: SaveGame::new(0),
+            grid_position: (0, 0),
+
+            coins: 0,
+            coins_collected: false,
+
+            section_weights: SectionWeights::default(),
 
             seen_progress: 0.0,
 
@@ -237,143 +449,4 @@ impl INode3D for Room {
     }
 
     fn ready(&mut self) {}
-
-    fn process(&mut self, _delta: f64) {
-        let room_position = self.base().get_position();
-        let player_position = if let Some(ref player) = self.player {
-            player.get_position()
-        } else {
-            room_position
-        };
-
-        let player_distance = Vector2::new(room_position.x, room_position.y)
-            .distance_to(Vector2::new(player_position.x, player_position.y));
-
-        if self.room_completely_revealed
-            || player_distance
-                > (self.current_game.player_properties.view_distance as f32
-                    + (ROOM_GRID_BASIS / 2.0))
-        {
-            return;
-        }
-
-        self.update_fog();
-    }
-
-    fn physics_process(&mut self, _delta: f64) {
-        let room_position = self.base().get_position();
-        let player_position = if let Some(ref player) = self.player {
-            player.get_position()
-        } else {
-            room_position
-        };
-
-        let player_distance = Vector2::new(room_position.x, room_position.y)
-            .distance_to(Vector2::new(player_position.x, player_position.y));
-
-        let half_grid_basis = ROOM_GRID_BASIS / 2.0;
-
-        if self.room_completely_revealed
-            || player_distance
-                > (self.current_game.player_properties.view_distance as f32 + half_grid_basis)
-        {
-            return;
-        }
-
-        if player_position.y < room_position.y + 1.5 && player_position.y > room_position.y - 1.5 {
-            self.seen_horizontal_corridors = true;
-            self.seen_center = true;
-        }
-
-        if player_position.x < room_position.x + 1.5 && player_position.x > room_position.x - 1.5 {
-            self.seen_vertical_corridors = true;
-            self.seen_center = true;
-        }
-
-        if player_position.x < room_position.x - half_grid_basis
-            && player_position.y > room_position.y - half_grid_basis
-            && player_position.y < room_position.y + half_grid_basis
-        {
-            self.seen_top_right = true;
-            self.seen_bottom_right = true;
-        } else if player_position.x > room_position.x - half_grid_basis
-            && player_position.x < room_position.x + half_grid_basis
-        {
-            if player_position.y < room_position.y - half_grid_basis {
-                self.seen_top_left = true;
-                self.seen_top_right = true;
-            } else if player_position.y > room_position.y - half_grid_basis
-                && player_position.y < room_position.y + half_grid_basis
-            {
-                self.seen_top_left = true;
-                self.seen_top_right = true;
-                self.seen_bottom_left = true;
-                self.seen_bottom_right = true;
-            } else if player_position.y > room_position.y + half_grid_basis {
-                self.seen_bottom_left = true;
-                self.seen_bottom_right = true;
-            }
-        } else if player_position.x > room_position.x + half_grid_basis
-            && player_position.y > room_position.y - half_grid_basis
-            && player_position.y < room_position.y + half_grid_basis
-        {
-            self.seen_top_left = true;
-            self.seen_bottom_left = true;
-        }
-
-        if player_distance < ROOM_GRID_BASIS {
-            if player_position.x < room_position.x - half_grid_basis
-                && player_position.y > room_position.y - half_grid_basis
-                && player_position.y < room_position.y + half_grid_basis
-            {
-                self.seen_vertical_corridors = true;
-            } else if player_position.x > room_position.x - half_grid_basis
-                && player_position.x < room_position.x + half_grid_basis
-            {
-                if player_position.y < room_position.y - half_grid_basis {
-                    self.seen_horizontal_corridors = true;
-                } else if player_position.y > room_position.y - half_grid_basis
-                    && player_position.y < room_position.y + half_grid_basis
-                {
-                    self.seen_vertical_corridors = true;
-                    self.seen_horizontal_corridors = true;
-                } else if player_position.y > room_position.y + half_grid_basis {
-                    self.seen_horizontal_corridors = true;
-                }
-            } else if player_position.x > room_position.x + half_grid_basis
-                && player_position.y > room_position.y - half_grid_basis
-                && player_position.y < room_position.y + half_grid_basis
-            {
-                self.seen_vertical_corridors = true;
-            }
-        }
-
-        self.seen_progress = if self.seen_horizontal_corridors {
-            0.25
-        } else {
-            0.0
-        } + if self.seen_vertical_corridors {
-            0.25
-        } else {
-            0.0
-        } + if self.seen_center { 0.3 } else { 0.0 }
-            + if self.seen_top_left { 0.05 } else { 0.0 }
-            + if self.seen_top_right { 0.05 } else { 0.0 }
-            + if self.seen_bottom_left { 0.05 } else { 0.0 }
-            + if self.seen_bottom_right { 0.05 } else { 0.0 };
-
-        if self.seen_horizontal_corridors
-            && self.seen_vertical_corridors
-            && self.seen_center
-            && self.seen_top_left
-            && self.seen_top_right
-            && self.seen_bottom_left
-            && self.seen_bottom_right
-        {
-            self.room_completely_revealed = true;
-            self.seen_progress = 1.0;
-
-            self.update_fog();
-        }
-    }
 }
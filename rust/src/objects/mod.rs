@@ -1,3 +1,5 @@
 pub mod cameras;
+pub mod extra_eye;
+pub mod ghost;
 pub mod map;
 pub mod player;
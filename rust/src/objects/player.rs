@@ -1,23 +1,14 @@
-use crate::managers::game::floor_manager::FloorLayout;
 use crate::managers::game::game_manager::GameState;
 use crate::objects::map::ROOM_GRID_BASIS;
+use crate::types::floor_layout::FloorLayout;
 use crate::types::input_bindings::InputBindings;
+use crate::types::movement::{MovementInput, MovementModel, MovementState};
+use crate::types::replay::ReplayInputs;
 use crate::types::save_game::SaveGame;
+use crate::types::simulation::SIMULATION_TICK_DELTA;
 use godot::classes::{AnimatedSprite3D, INode3D, Input, Node3D, TextureProgressBar};
 use godot::prelude::*;
 
-const UP_DIRECTION: (i64, i64) = (0, 1);
-const DOWN_DIRECTION: (i64, i64) = (0, -1);
-const LEFT_DIRECTION: (i64, i64) = (-1, 0);
-const RIGHT_DIRECTION: (i64, i64) = (1, 0);
-
-#[derive(Debug, PartialEq, Clone)]
-enum PlayerStatus {
-    Idle,
-    Moving,
-    Cooling,
-}
-
 #[derive(GodotClass)]
 #[class(base=Node3D)]
 pub struct Player {
@@ -26,47 +17,70 @@ pub struct Player {
 
     #[export]
     cooldown_ring: OnEditor<Gd<TextureProgressBar>>,
+    #[export]
+    redirector_ring: OnEditor<Gd<TextureProgressBar>>,
 
     game_state: GameState,
 
-    current_game: SaveGame,
-
-    floor_layout: FloorLayout,
-
-    player_status: PlayerStatus,
+    movement: MovementModel,
 
     is_running_animation: bool,
 
-    speed: f64,
-    cooldown: f64,
-    total_cooldown: f64,
+    extra_eyes_left: i64,
 
-    direction: (i64, i64),
+    last_input: MovementInput,
 
-    target_position: (i64, i64),
+    // every running tick's input, so the run can be saved as a replay
+    recorded_inputs: ReplayInputs,
+    // when set the player is driven from these instead of the keyboard
+    playback_inputs: Option<Vec<MovementInput>>,
+    running_tick: usize,
+
+    finish_requested: bool,
 
     base: Base<Node3D>,
 }
 
+#[godot_api]
+impl Player {
+    #[signal]
+    pub fn teleported(from: Vector2i, to: Vector2i);
+
+    #[signal]
+    pub fn extra_eye_deployed(from: Vector2i, direction: Vector2i, speed: f64);
+}
+
 impl Player {
     pub fn set_game_properties(&mut self, save_game: SaveGame, floor_layout: FloorLayout) {
-        self.current_game = save_game;
-        self.floor_layout = floor_layout;
+        self.movement = MovementModel::new(save_game.player_properties, floor_layout);
     }
 
     pub fn reset(&mut self) {
         self.is_running_animation = false;
 
-        self.speed = 0.0;
-        self.cooldown = 0.0;
-        self.total_cooldown = 0.0;
+        self.movement.reset();
 
-        self.direction = (0, 0);
+        self.last_input = MovementInput::default();
 
-        self.target_position = (0, 0);
+        self.recorded_inputs = ReplayInputs::default();
+        self.running_tick = 0;
+
+        self.finish_requested = false;
+
+        self.extra_eyes_left = self
+            .movement
+            .get_properties()
+            .extra_eye
+            .as_ref()
+            .map_or(0, |extra_eye| extra_eye.count);
 
         self.base_mut().set_position(Vector3::new(0.0, 0.0, 0.0));
         self.cooldown_ring.set_value(0.0);
+        self.redirector_ring.set_value(0.0);
+
+        let has_redirector = self.movement.get_properties().momentum_redirector.is_some();
+
+        self.redirector_ring.set_visible(has_redirector);
 
         self.sprite.set_animation("default");
         self.sprite.play();
@@ -76,38 +90,117 @@ impl Player {
         self.game_state = next_game_state;
     }
 
-    fn calculate_stop_cooldown(&self) -> f64 {
-        (self.speed.abs().powf(1.4) / 100.0)
-            * (self.current_game.player_properties.stopping_mass / 128.0)
+    pub fn set_playback(&mut self, playback_inputs: Option<Vec<MovementInput>>) {
+        self.playback_inputs = playback_inputs;
     }
 
-    fn change_player_status(&mut self, next_player_status: PlayerStatus) {
-        match next_player_status {
-            PlayerStatus::Idle => {}
-            PlayerStatus::Moving => {
-                let last_target_position = self.target_position;
+    pub fn get_movement_state(&self) -> MovementState {
+        *self.movement.get_state()
+    }
 
-                self.target_position = (
-                    last_target_position.0 + self.direction.0,
-                    last_target_position.1 + self.direction.1,
-                );
+    // only true on the tick the finish button went down
+    pub fn is_finish_requested(&self) -> bool {
+        self.finish_requested
+    }
 
-                if !self.floor_layout.contains_key(&self.target_position) {
-                    self.target_position = last_target_position;
-                    self.direction = (0, 0);
+    pub fn take_recorded_inputs(&mut self) -> ReplayInputs {
+        std::mem::take(&mut self.recorded_inputs)
+    }
 
-                    self.change_player_status(PlayerStatus::Cooling);
+    // one fixed simulation step while running, called by the game manager
+    pub fn simulate_tick(&mut self) {
+        let input = match self.playback_inputs {
+            Some(ref playback_inputs) => playback_inputs
+                .get(self.running_tick)
+                .copied()
+                .unwrap_or_default(),
+            None => Self::read_input(),
+        };
 
-                    return;
-                }
-            }
-            PlayerStatus::Cooling => {
-                self.cooldown = self.calculate_stop_cooldown();
-                self.total_cooldown = self.cooldown;
-            }
+        self.recorded_inputs.push(input);
+        self.running_tick += 1;
+
+        let pressed = input.get_pressed_since(&self.last_input);
+
+        if pressed.deploy_eye {
+            self.deploy_extra_eye(input);
+        }
+
+        self.finish_requested = pressed.finish_floor;
+
+        self.last_input = input;
+
+        let state = self.movement.tick(input, SIMULATION_TICK_DELTA);
+
+        self.base_mut().set_position(Vector3::new(
+            state.position.0 as f32,
+            state.position.1 as f32,
+            0.0,
+        ));
+
+        if let Some((entrance, destination)) = state.teleported {
+            self.signals().teleported().emit(
+                Vector2i::new(entrance.0 as i32, entrance.1 as i32),
+                Vector2i::new(destination.0 as i32, destination.1 as i32),
+            );
+        }
+    }
+
+    // sends an eye down the corridor the player is facing from the nearest room
+    fn deploy_extra_eye(&mut self, input: MovementInput) {
+        let Some(ref extra_eye) = self.movement.get_properties().extra_eye else {
+            return;
+        };
+
+        let speed = extra_eye.initial_speed;
+
+        let state = self.movement.get_state();
+
+        let Some(direction) = input
+            .get_direction()
+            .or((state.direction != (0, 0)).then_some(state.direction))
+        else {
+            return;
+        };
+
+        let from = (
+            (state.position.0 / ROOM_GRID_BASIS as f64).round() as i64,
+            (state.position.1 / ROOM_GRID_BASIS as f64).round() as i64,
+        );
+
+        if self.extra_eyes_left <= 0
+            || !self
+                .movement
+                .get_floor_layout()
+                .has_corridor(from, (from.0 + direction.0, from.1 + direction.1))
+        {
+            return;
         }
 
-        self.player_status = next_player_status;
+        self.extra_eyes_left -= 1;
+
+        self.signals().extra_eye_deployed().emit(
+            Vector2i::new(from.0 as i32, from.1 as i32),
+            Vector2i::new(direction.0 as i32, direction.1 as i32),
+            speed,
+        );
+    }
+
+    fn read_input() -> MovementInput {
+        let input = Input::singleton();
+
+        MovementInput {
+            up: input.is_action_pressed(Into::<&str>::into(InputBindings::PlayerMoveUp)),
+            down: input.is_action_pressed(Into::<&str>::into(InputBindings::PlayerMoveDown)),
+            left: input.is_action_pressed(Into::<&str>::into(InputBindings::PlayerMoveLeft)),
+            right: input.is_action_pressed(Into::<&str>::into(InputBindings::PlayerMoveRight)),
+
+            redirect: input.is_action_pressed(Into::<&str>::into(InputBindings::PlayerRedirect)),
+            deploy_eye: input.is_action_pressed(Into::<&str>::into(InputBindings::PlayerDeployEye)),
+
+            finish_floor: input
+                .is_action_pressed(Into::<&str>::into(InputBindings::GameFinishFloor)),
+        }
     }
 }
 
@@ -118,24 +211,23 @@ impl INode3D for Player {
             sprite: OnEditor::default(),
 
             cooldown_ring: OnEditor::default(),
+            redirector_ring: OnEditor::default(),
 
             game_state: GameState::Loading,
 
-            current_game: SaveGame::new(0),
-
-            floor_layout: FloorLayout::default(),
-
-            player_status: PlayerStatus::Idle,
+            movement: MovementModel::default(),
 
             is_running_animation: false,
 
-            speed: 0.0,
-            cooldown: 0.0,
-            total_cooldown: 0.0,
+            extra_eyes_left: 0,
+
+            last_input: MovementInput::default(),
 
-            direction: (0, 0),
+            recorded_inputs: ReplayInputs::default(),
+            playback_inputs: None,
+            running_tick: 0,
 
-            target_position: (0, 0),
+            finish_requested: false,
 
             base,
         }
@@ -146,7 +238,9 @@ impl INode3D for Player {
     }
 
     fn process(&mut self, _delta: f64) {
-        let should_running_animation = self.speed.abs() > 0.1;
+        let state = *self.movement.get_state();
+
+        let should_running_animation = state.speed.abs() > 0.1;
 
         if should_running_animation != self.is_running_animation {
             self.sprite.set_animation(if should_running_animation {
@@ -161,9 +255,9 @@ impl INode3D for Player {
         self.sprite
             .get_sprite_frames()
             .unwrap()
-            .set_animation_speed("run", 5.0 + (self.speed * 2.0));
+            .set_animation_speed("run", 5.0 + (state.speed * 2.0));
 
-        match self.direction {
+        match state.direction {
             (-1, 0) => {
                 self.sprite
                     .set_rotation_degrees(Vector3::new(0.0, 0.0, 180.0));
@@ -185,138 +279,21 @@ impl INode3D for Player {
         match self.game_state {
             GameState::Running => {
                 self.cooldown_ring
-                    .set_value((self.cooldown / self.total_cooldown.max(0.001)) * 100.0);
-            }
-            _ => {
-                self.cooldown_ring.set_value(0.0);
-            }
-        }
-    }
-
-    fn physics_process(&mut self, delta: f64) {
-        let is_pressing_up: bool =
-            Input::singleton().is_action_pressed(Into::<&str>::into(InputBindings::PlayerMoveUp));
-        let is_pressing_down: bool =
-            Input::singleton().is_action_pressed(Into::<&str>::into(InputBindings::PlayerMoveDown));
-        let is_pressing_left: bool =
-            Input::singleton().is_action_pressed(Into::<&str>::into(InputBindings::PlayerMoveLeft));
-        let is_pressing_right: bool = Input::singleton()
-            .is_action_pressed(Into::<&str>::into(InputBindings::PlayerMoveRight));
-
-        if self.game_state != GameState::Running {
-            return;
-        }
-
-        match self.player_status {
-            PlayerStatus::Idle => {
-                if is_pressing_up {
-                    self.direction = UP_DIRECTION;
-
-                    self.change_player_status(PlayerStatus::Moving);
-                } else if is_pressing_down {
-                    self.direction = DOWN_DIRECTION;
+                    .set_value((state.cooldown / state.total_cooldown.max(0.001)) * 100.0);
 
-                    self.change_player_status(PlayerStatus::Moving);
-                } else if is_pressing_left {
-                    self.direction = LEFT_DIRECTION;
+                let redirector_cooldown = self
+                    .movement
+                    .get_properties()
+                    .momentum_redirector
+                    .as_ref()
+                    .map_or(0.001, |redirector| redirector.cooldown.max(0.001));
 
-                    self.change_player_status(PlayerStatus::Moving);
-                } else if is_pressing_right {
-                    self.direction = RIGHT_DIRECTION;
-
-                    self.change_player_status(PlayerStatus::Moving);
-                }
-            }
-            PlayerStatus::Moving => {
-                self.speed = (self.speed
-                    + (self.current_game.player_properties.active_acceleration * delta))
-                    .min(self.current_game.player_properties.max_speed);
-
-                let speed_vector = Vector3::new(
-                    (self.speed as f32 * self.direction.0 as f32) * delta as f32,
-                    (self.speed as f32 * self.direction.1 as f32) * delta as f32,
-                    0.0,
-                );
-
-                let position = self.base().get_position();
-
-                self.base_mut().set_position(position + speed_vector);
-
-                let position = self.base().get_position();
-
-                // have we passed the target position
-                if (self.direction == UP_DIRECTION
-                    && position.y >= self.target_position.1 as f32 * ROOM_GRID_BASIS)
-                    || (self.direction == DOWN_DIRECTION
-                        && position.y <= self.target_position.1 as f32 * ROOM_GRID_BASIS)
-                    || (self.direction == LEFT_DIRECTION
-                        && position.x <= self.target_position.0 as f32 * ROOM_GRID_BASIS)
-                    || (self.direction == RIGHT_DIRECTION
-                        && position.x >= self.target_position.0 as f32 * ROOM_GRID_BASIS)
-                {
-                    if is_pressing_up {
-                        if self.direction != UP_DIRECTION {
-                            self.change_player_status(PlayerStatus::Cooling);
-                        }
-
-                        self.direction = UP_DIRECTION;
-                    } else if is_pressing_down {
-                        if self.direction != DOWN_DIRECTION {
-                            self.change_player_status(PlayerStatus::Cooling);
-                        }
-
-                        self.direction = DOWN_DIRECTION;
-                    } else if is_pressing_left {
-                        if self.direction != LEFT_DIRECTION {
-                            self.change_player_status(PlayerStatus::Cooling);
-                        }
-
-                        self.direction = LEFT_DIRECTION;
-                    } else if is_pressing_right {
-                        if self.direction != RIGHT_DIRECTION {
-                            self.change_player_status(PlayerStatus::Cooling);
-                        }
-
-                        self.direction = RIGHT_DIRECTION;
-                    } else {
-                        self.change_player_status(PlayerStatus::Cooling);
-
-                        self.direction = (0, 0);
-                    }
-
-                    if self.direction != (0, 0) && self.player_status != PlayerStatus::Cooling {
-                        self.change_player_status(PlayerStatus::Moving);
-                    }
-                }
+                self.redirector_ring
+                    .set_value((state.redirect_cooldown / redirector_cooldown) * 100.0);
             }
-            PlayerStatus::Cooling => {
-                let target_position = self.target_position;
-
-                self.base_mut().set_position(Vector3::new(
-                    target_position.0 as f32 * ROOM_GRID_BASIS,
-                    target_position.1 as f32 * ROOM_GRID_BASIS,
-                    0.0,
-                ));
-
-                self.speed = 0.0;
-
-                if is_pressing_up {
-                    self.direction = UP_DIRECTION;
-                } else if is_pressing_down {
-                    self.direction = DOWN_DIRECTION;
-                } else if is_pressing_left {
-                    self.direction = LEFT_DIRECTION;
-                } else if is_pressing_right {
-                    self.direction = RIGHT_DIRECTION;
-                }
-
-                if self.cooldown > 0.0 {
-                    self.cooldown -= delta;
-                } else if self.direction != (0, 0) {
-                    self.change_player_status(PlayerStatus::Moving);
-                } else {
-                    self.change_player_status(PlayerStatus::Idle);
-                }
+            _ => {
+                self.cooldown_ring.set_value(0.0);
+                self.redirector_ring.set_value(0.0);
             }
         }
     }
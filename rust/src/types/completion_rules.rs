@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum CompletionRulesError {
+    #[error("invalid completion rules: {0}")]
+    InvalidFormat(#[from] serde_json::Error),
+
+    #[error("section weights add up to {0} instead of 1")]
+    InvalidSectionWeights(f64),
+
+    #[error("section weight for {0} is {1}, below 0")]
+    NegativeSectionWeight(&'static str, f64),
+
+    #[error("completion threshold {1} from floor {0} is not above 0 and at most 1")]
+    InvalidThreshold(i64, f64),
+}
+
+// how much of a room's seen_progress each part of it is worth
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SectionWeights {
+    pub horizontal_corridors: f64,
+    pub vertical_corridors: f64,
+
+    pub center: f64,
+
+    // each of the four
+    pub corner: f64,
+}
+
+impl Default for SectionWeights {
+    fn default() -> Self {
+        Self {
+            horizontal_corridors: 0.25,
+            vertical_corridors: 0.25,
+
+            center: 0.3,
+
+            corner: 0.05,
+        }
+    }
+}
+
+impl SectionWeights {
+    fn get_named(&self) -> [(&'static str, f64); 4] {
+        [
+            ("horizontal_corridors", self.horizontal_corridors),
+            ("vertical_corridors", self.vertical_corridors),
+            ("center", self.center),
+            ("corner", self.corner),
+        ]
+    }
+
+    fn get_total(&self) -> f64 {
+        self.horizontal_corridors + self.vertical_corridors + self.center + self.corner * 4.0
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CompletionThreshold {
+    pub from_floor: i64,
+
+    // share of the floor that has to be seen for it to count as done
+    pub threshold: f64,
+}
+
+// fields missing from the file fall back to their defaults
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompletionRules {
+    pub section_weights: SectionWeights,
+
+    // the entry with the highest from_floor at or below the current floor applies
+    pub thresholds: Vec<CompletionThreshold>,
+
+    // paid on top of the usual score when the whole floor was seen
+    pub full_reveal_bonus: i64,
+}
+
+impl Default for CompletionRules {
+    fn default() -> Self {
+        Self {
+            section_weights: SectionWeights::default(),
+
+            thresholds: vec![CompletionThreshold {
+                from_floor: 0,
+                threshold: 1.0,
+            }],
+
+            full_reveal_bonus: 0,
+        }
+    }
+}
+
+impl CompletionRules {
+    pub fn parse(text: &str) -> Result<CompletionRules, CompletionRulesError> {
+        let completion_rules = serde_json::from_str::<CompletionRules>(text)?;
+
+        // a negative weight would let seeing more of a room lower its progress
+        for (name, weight) in completion_rules.section_weights.get_named() {
+            if weight < 0.0 {
+                return Err(CompletionRulesError::NegativeSectionWeight(name, weight));
+            }
+        }
+
+        let total_weight = completion_rules.section_weights.get_total();
+
+        if (total_weight - 1.0).abs() > 0.001 {
+            return Err(CompletionRulesError::InvalidSectionWeights(total_weight));
+        }
+
+        for threshold in completion_rules.thresholds.iter() {
+            if threshold.threshold <= 0.0 || threshold.threshold > 1.0 {
+                return Err(CompletionRulesError::InvalidThreshold(
+                    threshold.from_floor,
+                    threshold.threshold,
+                ));
+            }
+        }
+
+        Ok(completion_rules)
+    }
+
+    pub fn get_threshold(&self, floor: i64) -> f64 {
+        self.thresholds
+            .iter()
+            .filter(|threshold| threshold.from_floor <= floor)
+            .max_by_key(|threshold| threshold.from_floor)
+            .map_or(1.0, |threshold| threshold.threshold)
+    }
+}
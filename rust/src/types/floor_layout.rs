@@ -0,0 +1,176 @@
+use crate::types::rooms::RoomType;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+pub const ROOM_GRID_BASIS: f32 = 8.0;
+
+pub const ROOM_NEIGHBOURS: [(i64, i64); 4] = [(0, 1), (0, -1), (-1, 0), (1, 0)];
+
+// how far from a room's center a viewer still counts as standing in it rather than a corridor
+const ROOM_HALF_WIDTH: f64 = 1.5;
+
+#[derive(Clone, Debug, Default)]
+pub struct FloorLayout {
+    rooms: HashMap<(i64, i64), RoomType>,
+
+    // directed, a two way corridor is stored once in each direction
+    corridors: HashSet<((i64, i64), (i64, i64))>,
+}
+
+impl FloorLayout {
+    pub fn new() -> FloorLayout {
+        FloorLayout::default()
+    }
+
+    pub fn add_room(&mut self, position: (i64, i64), room_type: RoomType) {
+        self.rooms.insert(position, room_type);
+    }
+
+    pub fn get_room(&self, position: &(i64, i64)) -> Option<&RoomType> {
+        self.rooms.get(position)
+    }
+
+    pub fn contains_room(&self, position: &(i64, i64)) -> bool {
+        self.rooms.contains_key(position)
+    }
+
+    pub fn rooms(&self) -> impl Iterator<Item = (&(i64, i64), &RoomType)> {
+        self.rooms.iter()
+    }
+
+    pub fn room_count(&self) -> usize {
+        self.rooms.len()
+    }
+
+    pub fn connect(&mut self, a: (i64, i64), b: (i64, i64)) {
+        self.connect_one_way(a, b);
+        self.connect_one_way(b, a);
+    }
+
+    pub fn connect_one_way(&mut self, from: (i64, i64), to: (i64, i64)) {
+        if (from.0 - to.0).abs() + (from.1 - to.1).abs() != 1
+            || !self.rooms.contains_key(&from)
+            || !self.rooms.contains_key(&to)
+        {
+            return;
+        }
+
+        self.corridors.insert((from, to));
+    }
+
+    pub fn connect_adjacent(&mut self) {
+        let positions: Vec<(i64, i64)> = self.rooms.keys().copied().collect();
+
+        for position in positions {
+            for direction in ROOM_NEIGHBOURS {
+                self.connect_one_way(
+                    position,
+                    (position.0 + direction.0, position.1 + direction.1),
+                );
+            }
+        }
+    }
+
+    pub fn get_exits(&self, position: (i64, i64)) -> impl Iterator<Item = (i64, i64)> {
+        ROOM_NEIGHBOURS
+            .iter()
+            .map(move |direction| (position.0 + direction.0, position.1 + direction.1))
+            .filter(move |next| self.can_move(position, *next))
+    }
+
+    // walking distance only, teleporters are not followed
+    pub fn get_distances(&self, start: (i64, i64)) -> HashMap<(i64, i64), i64> {
+        let mut distances = HashMap::from([(start, 0)]);
+        let mut queue = VecDeque::from([start]);
+
+        while let Some(position) = queue.pop_front() {
+            let distance = distances[&position];
+
+            for next in self.get_exits(position) {
+                if let Entry::Vacant(entry) = distances.entry(next) {
+                    entry.insert(distance + 1);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        distances
+    }
+
+    pub fn get_teleport_destination(&self, position: &(i64, i64)) -> Option<(i64, i64)> {
+        self.rooms
+            .get(position)
+            .and_then(|room_type| room_type.get_teleport_destination())
+    }
+
+    pub fn get_stop_penalty(&self, position: &(i64, i64)) -> f64 {
+        self.rooms
+            .get(position)
+            .map_or(0.0, |room_type| room_type.get_stop_penalty())
+    }
+
+    pub fn can_move(&self, from: (i64, i64), to: (i64, i64)) -> bool {
+        self.corridors.contains(&(from, to))
+    }
+
+    // corridors are open to look through in either direction
+    pub fn has_corridor(&self, a: (i64, i64), b: (i64, i64)) -> bool {
+        self.corridors.contains(&(a, b)) || self.corridors.contains(&(b, a))
+    }
+
+    // rooms in line of sight of a world position, looking straight down open corridors
+    // so walls and corners block the view; from inside a corridor only along it
+    pub fn get_visible_rooms(
+        &self,
+        viewer_position: (f64, f64),
+        view_range: f64,
+    ) -> Vec<(i64, i64)> {
+        let grid_basis = ROOM_GRID_BASIS as f64;
+
+        let position = (
+            (viewer_position.0 / grid_basis).round() as i64,
+            (viewer_position.1 / grid_basis).round() as i64,
+        );
+
+        if !self.contains_room(&position) {
+            return vec![];
+        }
+
+        let offset = (
+            viewer_position.0 - position.0 as f64 * grid_basis,
+            viewer_position.1 - position.1 as f64 * grid_basis,
+        );
+
+        let directions: &[(i64, i64)] =
+            if offset.0.abs() <= ROOM_HALF_WIDTH && offset.1.abs() <= ROOM_HALF_WIDTH {
+                &ROOM_NEIGHBOURS
+            } else if offset.0.abs() > offset.1.abs() {
+                &[(-1, 0), (1, 0)]
+            } else {
+                &[(0, 1), (0, -1)]
+            };
+
+        let mut visible = vec![position];
+
+        for direction in directions {
+            let mut current = position;
+
+            loop {
+                let next = (current.0 + direction.0, current.1 + direction.1);
+
+                let distance = (next.0 as f64 * grid_basis - viewer_position.0)
+                    .hypot(next.1 as f64 * grid_basis - viewer_position.1);
+
+                if distance > view_range || !self.has_corridor(current, next) {
+                    break;
+                }
+
+                visible.push(next);
+
+                current = next;
+            }
+        }
+
+        visible
+    }
+}
@@ -0,0 +1,116 @@
+use crate::types::completion_rules::{CompletionRules, SectionWeights};
+use serde::{Deserialize, Serialize};
+
+pub const GHOST_SAMPLE_INTERVAL: f64 = 1.0 / 30.0;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct GhostSample {
+    pub position: (f32, f32),
+
+    // floor completion at the time of the sample
+    pub progress: f32,
+}
+
+// where the player was during a run, sampled at a fixed interval from the start
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GhostTrace {
+    pub level_seed: i64,
+    pub floor: i64,
+
+    // what the floor had to be seen to when the ghost ran it, ghosts from before these were
+    // kept never match and get replaced
+    #[serde(default)]
+    pub completion_threshold: f64,
+    #[serde(default)]
+    pub section_weights: SectionWeights,
+
+    pub sample_interval: f64,
+
+    pub final_time: f64,
+
+    pub samples: Vec<GhostSample>,
+}
+
+impl GhostTrace {
+    pub fn new(level_seed: i64, floor: i64, completion_rules: &CompletionRules) -> GhostTrace {
+        GhostTrace {
+            level_seed,
+            floor,
+
+            completion_threshold: completion_rules.get_threshold(floor),
+            section_weights: completion_rules.section_weights.clone(),
+
+            sample_interval: GHOST_SAMPLE_INTERVAL,
+
+            final_time: 0.0,
+
+            samples: vec![],
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<GhostTrace, serde_json::Error> {
+        serde_json::from_str::<GhostTrace>(text)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn get_file_name(level_seed: i64, floor: i64) -> String {
+        format!("seed_{level_seed}_floor_{floor}.ghost")
+    }
+
+    // called every tick, only keeps a sample once per interval
+    pub fn record(&mut self, time: f64, position: (f64, f64), progress: f64) {
+        if time < self.samples.len() as f64 * self.sample_interval {
+            return;
+        }
+
+        self.samples.push(GhostSample {
+            position: (position.0 as f32, position.1 as f32),
+            progress: progress as f32,
+        });
+    }
+
+    pub fn finish(&mut self, final_time: f64) {
+        self.final_time = final_time;
+    }
+
+    // times under other rules are not a fair race
+    pub fn was_played_under(&self, completion_rules: &CompletionRules) -> bool {
+        self.completion_threshold == completion_rules.get_threshold(self.floor)
+            && self.section_weights == completion_rules.section_weights
+    }
+
+    pub fn is_better_than(&self, other: &GhostTrace) -> bool {
+        self.final_time < other.final_time
+    }
+
+    // linear between samples, and the ghost waits at its last position once it is done
+    pub fn get_position_at(&self, time: f64) -> Option<(f64, f64)> {
+        let last = self.samples.last()?;
+
+        let index = (time.max(0.0) / self.sample_interval).floor() as usize;
+
+        let (Some(from), Some(to)) = (self.samples.get(index), self.samples.get(index + 1)) else {
+            return Some((last.position.0 as f64, last.position.1 as f64));
+        };
+
+        let weight = ((time / self.sample_interval) - index as f64) as f32;
+
+        Some((
+            (from.position.0 + (to.position.0 - from.position.0) * weight) as f64,
+            (from.position.1 + (to.position.1 - from.position.1) * weight) as f64,
+        ))
+    }
+
+    // when the ghost first got as far through the floor, or its final time if it never did
+    pub fn get_time_at_progress(&self, progress: f64) -> f64 {
+        self.samples
+            .iter()
+            .position(|sample| sample.progress as f64 >= progress)
+            .map_or(self.final_time, |index| {
+                (index as f64 * self.sample_interval).min(self.final_time)
+            })
+    }
+}
@@ -4,7 +4,12 @@ pub enum InputBindings {
     PlayerMoveLeft,
     PlayerMoveRight,
 
+    PlayerRedirect,
+    PlayerDeployEye,
+
     GamePause,
+    GameToggleMinimap,
+    GameFinishFloor,
 }
 
 impl From<InputBindings> for &str {
@@ -15,7 +20,12 @@ impl From<InputBindings> for &str {
             InputBindings::PlayerMoveLeft => "player_move_left",
             InputBindings::PlayerMoveRight => "player_move_right",
 
+            InputBindings::PlayerRedirect => "player_redirect",
+            InputBindings::PlayerDeployEye => "player_deploy_eye",
+
             InputBindings::GamePause => "game_pause",
+            InputBindings::GameToggleMinimap => "game_toggle_minimap",
+            InputBindings::GameFinishFloor => "game_finish_floor",
         }
     }
 }
@@ -1,5 +1,13 @@
+pub mod completion_rules;
+pub mod floor_layout;
+pub mod ghost;
 pub mod input_bindings;
+pub mod movement;
 pub mod player_properties;
+pub mod replay;
+pub mod room_sections;
 pub mod rooms;
 pub mod save_game;
+pub mod seed;
+pub mod simulation;
 pub mod upgrades;
@@ -0,0 +1,934 @@
+use crate::types::floor_layout::{FloorLayout, ROOM_GRID_BASIS};
+use crate::types::player_properties::PlayerProperties;
+
+pub const UP_DIRECTION: (i64, i64) = (0, 1);
+pub const DOWN_DIRECTION: (i64, i64) = (0, -1);
+pub const LEFT_DIRECTION: (i64, i64) = (-1, 0);
+pub const RIGHT_DIRECTION: (i64, i64) = (1, 0);
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum PlayerStatus {
+    Idle,
+    Moving,
+    Cooling,
+}
+
+// the buttons held down during one tick
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct MovementInput {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+
+    pub redirect: bool,
+    pub deploy_eye: bool,
+
+    // ends the run early once enough of the floor has been seen
+    pub finish_floor: bool,
+}
+
+impl MovementInput {
+    pub fn to_bits(&self) -> u8 {
+        [
+            self.up,
+            self.down,
+            self.left,
+            self.right,
+            self.redirect,
+            self.deploy_eye,
+            self.finish_floor,
+        ]
+        .iter()
+        .enumerate()
+        .fold(0, |bits, (i, pressed)| bits | ((*pressed as u8) << i))
+    }
+
+    pub fn from_bits(bits: u8) -> MovementInput {
+        MovementInput {
+            up: bits & 1 != 0,
+            down: bits & (1 << 1) != 0,
+            left: bits & (1 << 2) != 0,
+            right: bits & (1 << 3) != 0,
+
+            redirect: bits & (1 << 4) != 0,
+            deploy_eye: bits & (1 << 5) != 0,
+
+            finish_floor: bits & (1 << 6) != 0,
+        }
+    }
+
+    // earlier directions win when several are held, same as they always have
+    pub fn get_direction(&self) -> Option<(i64, i64)> {
+        if self.up {
+            Some(UP_DIRECTION)
+        } else if self.down {
+            Some(DOWN_DIRECTION)
+        } else if self.left {
+            Some(LEFT_DIRECTION)
+        } else if self.right {
+            Some(RIGHT_DIRECTION)
+        } else {
+            None
+        }
+    }
+
+    // only the buttons that went down this tick
+    pub fn get_pressed_since(&self, previous: &MovementInput) -> MovementInput {
+        MovementInput {
+            up: self.up && !previous.up,
+            down: self.down && !previous.down,
+            left: self.left && !previous.left,
+            right: self.right && !previous.right,
+
+            redirect: self.redirect && !previous.redirect,
+            deploy_eye: self.deploy_eye && !previous.deploy_eye,
+
+            finish_floor: self.finish_floor && !previous.finish_floor,
+        }
+    }
+}
+
+// a direction change the player set off in
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct MovementTurn {
+    pub direction: (i64, i64),
+
+    // the player had to sit out a stop cooldown before leaving
+    pub had_cooldown: bool,
+
+    // when the direction was pressed relative to reaching the room, negative is early
+    pub press_offset: Option<f64>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct MovementState {
+    pub status: PlayerStatus,
+
+    // world units, a room sits at its grid position times ROOM_GRID_BASIS
+    pub position: (f64, f64),
+
+    pub speed: f64,
+    pub direction: (i64, i64),
+
+    pub target_position: (i64, i64),
+
+    pub cooldown: f64,
+    pub total_cooldown: f64,
+
+    // the last direction pressed and how long it is remembered for
+    pub move_buffer: Option<((i64, i64), f64)>,
+
+    // time until the momentum redirector can be used again
+    pub redirect_cooldown: f64,
+
+    // only set on the tick the player went through a teleporter, as (entrance, exit)
+    pub teleported: Option<((i64, i64), (i64, i64))>,
+
+    // only set on the tick the player set off in a new direction
+    pub turned: Option<MovementTurn>,
+}
+
+impl Default for MovementState {
+    fn default() -> Self {
+        Self {
+            status: PlayerStatus::Idle,
+
+            position: (0.0, 0.0),
+
+            speed: 0.0,
+            direction: (0, 0),
+
+            target_position: (0, 0),
+
+            cooldown: 0.0,
+            total_cooldown: 0.0,
+
+            move_buffer: None,
+
+            redirect_cooldown: 0.0,
+
+            teleported: None,
+
+            turned: None,
+        }
+    }
+}
+
+// the player's Idle/Moving/Cooling state machine, kept engine-free so it can be
+// simulated without godot; the Player node only feeds it input and draws the result
+#[derive(Clone, Debug, Default)]
+pub struct MovementModel {
+    state: MovementState,
+
+    last_input: MovementInput,
+
+    // simulated time at the start of the current tick
+    clock: f64,
+
+    last_direction_press: Option<f64>,
+    last_arrival: Option<f64>,
+    last_travel_direction: (i64, i64),
+
+    properties: PlayerProperties,
+    floor_layout: FloorLayout,
+}
+
+impl MovementModel {
+    pub fn new(properties: PlayerProperties, floor_layout: FloorLayout) -> MovementModel {
+        MovementModel {
+            state: MovementState::default(),
+
+            last_input: MovementInput::default(),
+
+            clock: 0.0,
+
+            last_direction_press: None,
+            last_arrival: None,
+            last_travel_direction: (0, 0),
+
+            properties,
+            floor_layout,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.state = MovementState::default();
+        self.last_input = MovementInput::default();
+
+        self.clock = 0.0;
+
+        self.last_direction_press = None;
+        self.last_arrival = None;
+        self.last_travel_direction = (0, 0);
+    }
+
+    pub fn get_state(&self) -> &MovementState {
+        &self.state
+    }
+
+    pub fn get_properties(&self) -> &PlayerProperties {
+        &self.properties
+    }
+
+    pub fn get_floor_layout(&self) -> &FloorLayout {
+        &self.floor_layout
+    }
+
+    pub fn tick(&mut self, input: MovementInput, delta: f64) -> MovementState {
+        self.state.teleported = None;
+        self.state.turned = None;
+
+        if input
+            .get_pressed_since(&self.last_input)
+            .get_direction()
+            .is_some()
+        {
+            self.last_direction_press = Some(self.clock);
+        }
+
+        self.update_move_buffer(input, delta);
+
+        self.state.redirect_cooldown = (self.state.redirect_cooldown - delta).max(0.0);
+
+        match self.state.status {
+            PlayerStatus::Idle => {
+                if let Some(direction) = input.get_direction() {
+                    self.state.direction = direction;
+
+                    self.change_status(PlayerStatus::Moving);
+                }
+            }
+            PlayerStatus::Moving => self.advance(input, delta),
+            PlayerStatus::Cooling => {
+                self.state.position = Self::get_world_position(self.state.target_position);
+
+                self.state.speed = 0.0;
+
+                if let Some(direction) = input.get_direction() {
+                    self.state.direction = direction;
+                }
+
+                if self.state.cooldown > 0.0 {
+                    self.state.cooldown -= delta;
+                } else if self.state.direction != (0, 0) {
+                    self.change_status(PlayerStatus::Moving);
+                } else {
+                    self.change_status(PlayerStatus::Idle);
+                }
+            }
+        }
+
+        self.clock += delta;
+
+        self.state
+    }
+
+    pub fn get_world_position(position: (i64, i64)) -> (f64, f64) {
+        (
+            position.0 as f64 * ROOM_GRID_BASIS as f64,
+            position.1 as f64 * ROOM_GRID_BASIS as f64,
+        )
+    }
+
+    // how long a straight run of `distance` takes from `speed`, accelerating until max_speed
+    pub fn get_travel_time(properties: &PlayerProperties, speed: f64, distance: f64) -> f64 {
+        // the quadratic below is 0 / 0 when standing still with nowhere to go
+        if distance <= 0.0 {
+            return 0.0;
+        }
+
+        let acceleration = properties.active_acceleration;
+        let max_speed = properties.max_speed;
+
+        let speed = speed.min(max_speed);
+
+        let time_to_max_speed = if acceleration > 0.0 {
+            (max_speed - speed) / acceleration
+        } else {
+            0.0
+        };
+
+        let accelerating_distance = Self::get_travel_distance(properties, speed, time_to_max_speed);
+
+        if distance <= accelerating_distance {
+            // the stable form of the quadratic, it also holds when there is no acceleration
+            2.0 * distance / (speed + (speed * speed + 2.0 * acceleration * distance).sqrt())
+        } else {
+            time_to_max_speed + (distance - accelerating_distance) / max_speed
+        }
+    }
+
+    fn get_travel_distance(properties: &PlayerProperties, speed: f64, time: f64) -> f64 {
+        let acceleration = properties.active_acceleration;
+        let max_speed = properties.max_speed;
+
+        let speed = speed.min(max_speed);
+
+        let time_to_max_speed = if acceleration > 0.0 {
+            ((max_speed - speed) / acceleration).min(time)
+        } else {
+            0.0
+        };
+
+        speed * time_to_max_speed
+            + 0.5 * acceleration * time_to_max_speed * time_to_max_speed
+            + (speed + acceleration * time_to_max_speed) * (time - time_to_max_speed)
+    }
+
+    fn get_speed_after(properties: &PlayerProperties, speed: f64, time: f64) -> f64 {
+        (speed + properties.active_acceleration * time).min(properties.max_speed)
+    }
+
+    // moves for `time`, handling each room on the way at the exact moment it is reached
+    // and spending whatever is left of the tick on what comes after it
+    fn advance(&mut self, input: MovementInput, mut time: f64) {
+        let tick_end = self.clock + time;
+
+        while self.state.status == PlayerStatus::Moving {
+            let target = Self::get_world_position(self.state.target_position);
+            let direction = self.state.direction;
+
+            let distance = ((target.0 - self.state.position.0) * direction.0 as f64
+                + (target.1 - self.state.position.1) * direction.1 as f64)
+                .max(0.0);
+
+            let arrival_time = Self::get_travel_time(&self.properties, self.state.speed, distance);
+
+            if arrival_time > time {
+                let travelled = Self::get_travel_distance(&self.properties, self.state.speed, time);
+
+                self.state.position.0 += travelled * direction.0 as f64;
+                self.state.position.1 += travelled * direction.1 as f64;
+
+                self.state.speed = Self::get_speed_after(&self.properties, self.state.speed, time);
+
+                return;
+            }
+
+            self.state.position = target;
+            self.state.speed =
+                Self::get_speed_after(&self.properties, self.state.speed, arrival_time);
+
+            time -= arrival_time;
+
+            self.last_arrival = Some(tick_end - time);
+
+            self.arrive(input);
+        }
+
+        // a stop starts cooling down the moment the room is reached
+        if self.state.status == PlayerStatus::Cooling {
+            self.state.cooldown -= time;
+        }
+    }
+
+    fn arrive(&mut self, input: MovementInput) {
+        if let Some(destination) = self
+            .floor_layout
+            .get_teleport_destination(&self.state.target_position)
+        {
+            let entrance = self.state.target_position;
+
+            self.state.position = Self::get_world_position(destination);
+
+            self.state.target_position = destination;
+            self.state.teleported = Some((entrance, destination));
+        }
+
+        let target_position = self.state.target_position;
+        let slide_position = (
+            target_position.0 + self.state.direction.0,
+            target_position.1 + self.state.direction.1,
+        );
+
+        // ice keeps the player going for as long as there is somewhere to go
+        let is_forced_to_move = self
+            .floor_layout
+            .get_room(&target_position)
+            .is_some_and(|room_type| room_type.forces_movement())
+            && self.floor_layout.can_move(target_position, slide_position);
+
+        if is_forced_to_move {
+            self.change_status(PlayerStatus::Moving);
+
+            return;
+        }
+
+        let turn_direction = self.take_turn_direction(input);
+
+        if let Some(direction) = turn_direction
+            && direction != self.state.direction
+            && self.can_redirect(input, direction)
+        {
+            self.redirect(direction);
+
+            return;
+        }
+
+        match turn_direction {
+            Some(direction) => {
+                if self.state.direction != direction {
+                    self.change_status(PlayerStatus::Cooling);
+                }
+
+                self.state.direction = direction;
+            }
+            None => {
+                self.change_status(PlayerStatus::Cooling);
+
+                self.state.direction = (0, 0);
+            }
+        }
+
+        if self.state.direction != (0, 0) && self.state.status != PlayerStatus::Cooling {
+            self.change_status(PlayerStatus::Moving);
+        }
+    }
+
+    fn update_move_buffer(&mut self, input: MovementInput, delta: f64) {
+        let pressed = input.get_pressed_since(&self.last_input);
+
+        self.last_input = input;
+
+        if !self.properties.has_move_buffer {
+            self.state.move_buffer = None;
+
+            return;
+        }
+
+        if let Some(direction) = pressed.get_direction() {
+            self.state.move_buffer = Some((direction, self.properties.move_buffer_window));
+        } else if let Some((direction, time_left)) = self.state.move_buffer {
+            self.state.move_buffer =
+                (time_left - delta > 0.0).then_some((direction, time_left - delta));
+        }
+    }
+
+    // a held direction always wins, otherwise a recent press that was let go too early
+    fn take_turn_direction(&mut self, input: MovementInput) -> Option<(i64, i64)> {
+        let buffered_direction = self
+            .state
+            .move_buffer
+            .take()
+            .map(|(direction, _)| direction);
+
+        input.get_direction().or(buffered_direction)
+    }
+
+    fn can_redirect(&self, input: MovementInput, direction: (i64, i64)) -> bool {
+        let target_position = self.state.target_position;
+
+        input.redirect
+            && self.properties.momentum_redirector.is_some()
+            && self.state.redirect_cooldown <= 0.0
+            && self.floor_layout.can_move(
+                target_position,
+                (
+                    target_position.0 + direction.0,
+                    target_position.1 + direction.1,
+                ),
+            )
+    }
+
+    // turns on the spot at full speed, skipping the stop cooldown entirely
+    fn redirect(&mut self, direction: (i64, i64)) {
+        self.state.direction = direction;
+
+        self.state.redirect_cooldown = self
+            .properties
+            .momentum_redirector
+            .as_ref()
+            .map_or(0.0, |redirector| redirector.cooldown);
+
+        self.change_status(PlayerStatus::Moving);
+    }
+
+    fn get_stop_cooldown(&self) -> f64 {
+        self.properties.get_stop_cooldown(self.state.speed)
+            + self
+                .floor_layout
+                .get_stop_penalty(&self.state.target_position)
+    }
+
+    fn change_status(&mut self, next_status: PlayerStatus) {
+        match next_status {
+            // setting off again after a full stop counts as a new input even in the same direction
+            PlayerStatus::Idle => {
+                self.last_travel_direction = (0, 0);
+            }
+            PlayerStatus::Moving => {
+                let last_target_position = self.state.target_position;
+
+                self.state.target_position = (
+                    last_target_position.0 + self.state.direction.0,
+                    last_target_position.1 + self.state.direction.1,
+                );
+
+                if !self
+                    .floor_layout
+                    .can_move(last_target_position, self.state.target_position)
+                {
+                    self.state.target_position = last_target_position;
+                    self.state.direction = (0, 0);
+
+                    self.change_status(PlayerStatus::Cooling);
+
+                    return;
+                }
+
+                if self.state.direction != self.last_travel_direction {
+                    self.state.turned = Some(MovementTurn {
+                        direction: self.state.direction,
+                        had_cooldown: self.state.status == PlayerStatus::Cooling
+                            && self.state.total_cooldown > 0.0,
+                        press_offset: self
+                            .last_direction_press
+                            .zip(self.last_arrival)
+                            .map(|(press, arrival)| press - arrival),
+                    });
+
+                    self.last_travel_direction = self.state.direction;
+                }
+            }
+            PlayerStatus::Cooling => {
+                self.state.cooldown = self.get_stop_cooldown();
+                self.state.total_cooldown = self.state.cooldown;
+            }
+        }
+
+        self.state.status = next_status;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::player_properties::MomentumRedirectorProperties;
+    use crate::types::rooms::RoomType;
+    use crate::types::simulation::SIMULATION_TICK_DELTA;
+
+    const RIGHT: MovementInput = MovementInput {
+        up: false,
+        down: false,
+        left: false,
+        right: true,
+
+        redirect: false,
+        deploy_eye: false,
+
+        finish_floor: false,
+    };
+
+    // normal rooms at every position, joined to their neighbours
+    fn get_layout(positions: &[(i64, i64)]) -> FloorLayout {
+        let mut layout = FloorLayout::new();
+
+        for position in positions {
+            layout.add_room(*position, RoomType::Normal);
+        }
+
+        layout.connect_adjacent();
+
+        layout
+    }
+
+    fn get_corridor(length: i64) -> FloorLayout {
+        get_layout(&(0..length).map(|x| (x, 0)).collect::<Vec<_>>())
+    }
+
+    fn tick_for(model: &mut MovementModel, input: MovementInput, time: f64) {
+        for _ in 0..(time / SIMULATION_TICK_DELTA).round() as i64 {
+            model.tick(input, SIMULATION_TICK_DELTA);
+        }
+    }
+
+    // ticks until the player is sitting in a room, returning how long that took
+    fn tick_until_stopped(model: &mut MovementModel, input: MovementInput) -> f64 {
+        let mut time = 0.0;
+
+        while model.get_state().status == PlayerStatus::Moving {
+            model.tick(input, SIMULATION_TICK_DELTA);
+
+            time += SIMULATION_TICK_DELTA;
+
+            assert!(time < 60.0, "player never stopped");
+        }
+
+        time
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-6,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn accelerates_up_to_max_speed() {
+        let properties = PlayerProperties::default();
+        let mut model = MovementModel::new(properties.clone(), get_corridor(32));
+
+        model.tick(RIGHT, SIMULATION_TICK_DELTA);
+
+        // a second in, well short of max_speed
+        tick_for(&mut model, RIGHT, 1.0);
+
+        assert_close(model.get_state().speed, properties.active_acceleration);
+
+        let mut top_speed: f64 = 0.0;
+
+        for _ in 0..(4.0 / SIMULATION_TICK_DELTA) as i64 {
+            top_speed = top_speed.max(model.tick(RIGHT, SIMULATION_TICK_DELTA).speed);
+        }
+
+        assert_close(top_speed, properties.max_speed);
+        assert_close(model.get_state().speed, properties.max_speed);
+    }
+
+    #[test]
+    fn stop_cooldown_scales_with_stopping_mass() {
+        let get_stop_cooldown = |stopping_mass: f64| {
+            let mut model = MovementModel::new(
+                PlayerProperties {
+                    stopping_mass,
+                    ..PlayerProperties::default()
+                },
+                get_corridor(4),
+            );
+
+            model.tick(RIGHT, SIMULATION_TICK_DELTA);
+
+            tick_until_stopped(&mut model, RIGHT);
+
+            let state = model.get_state();
+
+            assert_eq!(state.status, PlayerStatus::Cooling);
+            assert_eq!(state.target_position, (3, 0));
+
+            state.total_cooldown
+        };
+
+        let light_cooldown = get_stop_cooldown(64.0);
+        let heavy_cooldown = get_stop_cooldown(256.0);
+
+        assert!(light_cooldown > 0.0);
+        assert_close(heavy_cooldown, light_cooldown * 4.0);
+    }
+
+    #[test]
+    fn reversal_and_perpendicular_turns_both_stop_first() {
+        // a junction at (1, 0) with a way on and a way up
+        let layout = get_layout(&[(0, 0), (1, 0), (2, 0), (1, 1)]);
+
+        let turn = |input: MovementInput| {
+            let mut model = MovementModel::new(PlayerProperties::default(), layout.clone());
+
+            model.tick(RIGHT, SIMULATION_TICK_DELTA);
+
+            let travel_time = tick_until_stopped(&mut model, input);
+            let state = *model.get_state();
+
+            assert!(travel_time > 0.0);
+            assert_eq!(state.status, PlayerStatus::Cooling);
+            assert_eq!(state.position, MovementModel::get_world_position((1, 0)));
+
+            let total_cooldown = state.total_cooldown;
+
+            let mut turned = None;
+
+            while turned.is_none() {
+                turned = model.tick(input, SIMULATION_TICK_DELTA).turned;
+            }
+
+            (
+                total_cooldown,
+                turned.unwrap(),
+                model.get_state().target_position,
+            )
+        };
+
+        let left = MovementInput {
+            left: true,
+            ..MovementInput::default()
+        };
+        let up = MovementInput {
+            up: true,
+            ..MovementInput::default()
+        };
+
+        let (reversal_cooldown, reversal, reversal_target) = turn(left);
+        let (perpendicular_cooldown, perpendicular, perpendicular_target) = turn(up);
+
+        // the stop is priced on speed alone, whichever way the player turns
+        assert!(reversal_cooldown > 0.0);
+        assert_close(reversal_cooldown, perpendicular_cooldown);
+
+        assert_eq!(reversal.direction, LEFT_DIRECTION);
+        assert!(reversal.had_cooldown);
+        assert_eq!(reversal_target, (0, 0));
+
+        assert_eq!(perpendicular.direction, UP_DIRECTION);
+        assert!(perpendicular.had_cooldown);
+        assert_eq!(perpendicular_target, (1, 1));
+    }
+
+    #[test]
+    fn holding_straight_keeps_moving_through_a_junction() {
+        let layout = get_layout(&[(0, 0), (1, 0), (2, 0), (1, 1)]);
+        let mut model = MovementModel::new(PlayerProperties::default(), layout);
+
+        model.tick(RIGHT, SIMULATION_TICK_DELTA);
+
+        while model.get_state().target_position == (1, 0) {
+            let state = model.tick(RIGHT, SIMULATION_TICK_DELTA);
+
+            assert_eq!(state.status, PlayerStatus::Moving);
+        }
+
+        assert_eq!(model.get_state().target_position, (2, 0));
+    }
+
+    #[test]
+    fn arrives_between_ticks() {
+        let properties = PlayerProperties::default();
+        let mut model = MovementModel::new(properties.clone(), get_corridor(2));
+
+        model.tick(RIGHT, SIMULATION_TICK_DELTA);
+
+        // one long tick that overshoots the room, the rest of it goes into the stop cooldown
+        let arrival_time = MovementModel::get_travel_time(&properties, 0.0, ROOM_GRID_BASIS as f64);
+        let leftover = 0.25;
+
+        let state = model.tick(MovementInput::default(), arrival_time + leftover);
+
+        assert_eq!(state.status, PlayerStatus::Cooling);
+        assert_eq!(state.position, MovementModel::get_world_position((1, 0)));
+        assert_close(state.cooldown, state.total_cooldown - leftover);
+        assert_close(
+            state.total_cooldown,
+            properties.get_stop_cooldown(properties.active_acceleration * arrival_time),
+        );
+    }
+
+    #[test]
+    fn passing_a_room_between_ticks_keeps_the_leftover_time() {
+        let properties = PlayerProperties::default();
+
+        let mut stepped = MovementModel::new(properties.clone(), get_corridor(8));
+        let mut jumped = MovementModel::new(properties, get_corridor(8));
+
+        stepped.tick(RIGHT, SIMULATION_TICK_DELTA);
+        jumped.tick(RIGHT, SIMULATION_TICK_DELTA);
+
+        let time = 240.0 * SIMULATION_TICK_DELTA;
+
+        tick_for(&mut stepped, RIGHT, time);
+        jumped.tick(RIGHT, time);
+
+        assert_close(
+            stepped.get_state().position.0,
+            jumped.get_state().position.0,
+        );
+        assert_close(stepped.get_state().speed, jumped.get_state().speed);
+        assert_eq!(
+            stepped.get_state().target_position,
+            jumped.get_state().target_position
+        );
+    }
+
+    #[test]
+    fn buffered_turn_is_taken_on_arrival() {
+        let properties = PlayerProperties {
+            has_move_buffer: true,
+            ..PlayerProperties::default()
+        };
+        let layout = get_layout(&[(0, 0), (1, 0), (2, 0), (1, 1)]);
+        let mut model = MovementModel::new(properties.clone(), layout);
+
+        model.tick(RIGHT, SIMULATION_TICK_DELTA);
+
+        // up is tapped and let go a little before the room, with nothing held when it is reached
+        let arrival_time = MovementModel::get_travel_time(&properties, 0.0, ROOM_GRID_BASIS as f64);
+
+        tick_for(
+            &mut model,
+            MovementInput::default(),
+            arrival_time - properties.move_buffer_window / 2.0,
+        );
+
+        let up = MovementInput {
+            up: true,
+            ..MovementInput::default()
+        };
+
+        let state = model.tick(up, SIMULATION_TICK_DELTA);
+
+        assert_eq!(state.status, PlayerStatus::Moving);
+        assert_eq!(
+            state.move_buffer.map(|(direction, _)| direction),
+            Some(UP_DIRECTION)
+        );
+
+        tick_until_stopped(&mut model, MovementInput::default());
+
+        let state = *model.get_state();
+
+        assert_eq!(state.status, PlayerStatus::Cooling);
+        assert_eq!(state.direction, UP_DIRECTION);
+        assert_eq!(state.move_buffer, None);
+
+        let mut turned = None;
+
+        while turned.is_none() {
+            turned = model
+                .tick(MovementInput::default(), SIMULATION_TICK_DELTA)
+                .turned;
+        }
+
+        assert_eq!(turned.map(|turn| turn.direction), Some(UP_DIRECTION));
+        assert_eq!(model.get_state().target_position, (1, 1));
+
+        // used up, so the next room is a plain stop
+        tick_until_stopped(&mut model, MovementInput::default());
+
+        assert_eq!(model.get_state().target_position, (1, 1));
+        assert_eq!(model.get_state().direction, (0, 0));
+    }
+
+    #[test]
+    fn buffered_turn_runs_out() {
+        let properties = PlayerProperties {
+            has_move_buffer: true,
+            ..PlayerProperties::default()
+        };
+        let layout = get_layout(&[(0, 0), (1, 0), (2, 0), (1, 1)]);
+        let mut model = MovementModel::new(properties.clone(), layout);
+
+        model.tick(RIGHT, SIMULATION_TICK_DELTA);
+
+        let up = MovementInput {
+            up: true,
+            ..MovementInput::default()
+        };
+
+        // tapped well before the window that reaches the room
+        model.tick(up, SIMULATION_TICK_DELTA);
+
+        tick_until_stopped(&mut model, MovementInput::default());
+
+        assert_eq!(model.get_state().direction, (0, 0));
+    }
+
+    #[test]
+    fn redirect_skips_the_stop_cooldown() {
+        let properties = PlayerProperties {
+            momentum_redirector: Some(MomentumRedirectorProperties::default()),
+            ..PlayerProperties::default()
+        };
+        let layout = get_layout(&[(0, 0), (1, 0), (2, 0), (1, 1), (0, 1)]);
+        let mut model = MovementModel::new(properties.clone(), layout);
+
+        model.tick(RIGHT, SIMULATION_TICK_DELTA);
+
+        let up_redirect = MovementInput {
+            up: true,
+            redirect: true,
+            ..MovementInput::default()
+        };
+
+        let mut turned = None;
+
+        while turned.is_none() {
+            let state = model.tick(up_redirect, SIMULATION_TICK_DELTA);
+
+            assert_eq!(state.status, PlayerStatus::Moving);
+
+            turned = state.turned;
+        }
+
+        let state = *model.get_state();
+
+        assert_eq!(turned.map(|turn| turn.had_cooldown), Some(false));
+        assert_eq!(state.direction, UP_DIRECTION);
+        assert_eq!(state.target_position, (1, 1));
+        assert!(state.speed > 0.0);
+        assert_close(
+            state.redirect_cooldown,
+            MomentumRedirectorProperties::default().cooldown,
+        );
+
+        // the redirector is still cooling down by the next room, so that turn is a normal stop
+        let left = MovementInput {
+            left: true,
+            redirect: true,
+            ..MovementInput::default()
+        };
+
+        tick_until_stopped(&mut model, left);
+
+        assert_eq!(model.get_state().status, PlayerStatus::Cooling);
+    }
+
+    #[test]
+    fn travel_time_is_zero_for_no_distance() {
+        let standing_still = PlayerProperties {
+            active_acceleration: 0.0,
+            ..PlayerProperties::default()
+        };
+
+        assert_eq!(
+            MovementModel::get_travel_time(&standing_still, 0.0, 0.0),
+            0.0
+        );
+        assert_eq!(
+            MovementModel::get_travel_time(&PlayerProperties::default(), 0.0, 0.0),
+            0.0
+        );
+        assert_eq!(
+            MovementModel::get_travel_time(&PlayerProperties::default(), 12.0, 0.0),
+            0.0
+        );
+    }
+}
@@ -1,10 +1,14 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+// fields missing from older saves fall back to their defaults
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PlayerProperties {
-    // pub has_move_buffer: bool,
-    // pub momentum_redirector: Option<MomentumRedirectorProperties>,
-    // pub extra_eye: Option<ExtraEyeProperties>,
+    pub has_move_buffer: bool,
+    pub move_buffer_window: f64,
+
+    pub momentum_redirector: Option<MomentumRedirectorProperties>,
+    pub extra_eye: Option<ExtraEyeProperties>,
     pub warmup_time: f64,
 
     pub max_speed: f64,
@@ -18,9 +22,11 @@ pub struct PlayerProperties {
 impl Default for PlayerProperties {
     fn default() -> Self {
         Self {
-            // has_move_buffer: false,
-            // momentum_redirector: None,
-            // extra_eye: None,
+            has_move_buffer: false,
+            move_buffer_window: 0.1,
+
+            momentum_redirector: None,
+            extra_eye: None,
             warmup_time: 5.0,
 
             max_speed: 24.0,
@@ -33,30 +39,36 @@ impl Default for PlayerProperties {
     }
 }
 
-// #[derive(Clone, Debug, Serialize, Deserialize)]
-// pub struct MomentumRedirectorProperties {
-//     cooldown: f64,
-// }
-
-// impl Default for MomentumRedirectorProperties {
-//     fn default() -> Self {
-//         MomentumRedirectorProperties { cooldown: 10.0 }
-//     }
-// }
-
-// #[derive(Clone, Debug, Serialize, Deserialize)]
-// pub struct ExtraEyeProperties {
-//     count: i64,
-
-//     initial_speed: f64,
-// }
-
-// impl Default for ExtraEyeProperties {
-//     fn default() -> Self {
-//         ExtraEyeProperties {
-//             count: 1,
-
-//             initial_speed: 16.0,
-//         }
-//     }
-// }
+impl PlayerProperties {
+    pub fn get_stop_cooldown(&self, speed: f64) -> f64 {
+        (speed.abs().powf(1.4) / 100.0) * (self.stopping_mass / 128.0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MomentumRedirectorProperties {
+    pub cooldown: f64,
+}
+
+impl Default for MomentumRedirectorProperties {
+    fn default() -> Self {
+        MomentumRedirectorProperties { cooldown: 10.0 }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExtraEyeProperties {
+    pub count: i64,
+
+    pub initial_speed: f64,
+}
+
+impl Default for ExtraEyeProperties {
+    fn default() -> Self {
+        ExtraEyeProperties {
+            count: 1,
+
+            initial_speed: 16.0,
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use crate::types::completion_rules::{CompletionRules, CompletionThreshold, SectionWeights};
+use crate::types::movement::MovementInput;
+use crate::types::player_properties::PlayerProperties;
+use crate::types::save_game::SaveGame;
+use crate::types::simulation::SIMULATION_TICK_RATE;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+// bump whenever the movement rules or this format change, old replays would desync
+pub const REPLAY_VERSION: u32 = 1;
+
+#[derive(Error, Debug)]
+pub enum ReplayError {
+    #[error("invalid replay file: {0}")]
+    InvalidFormat(#[from] serde_json::Error),
+
+    #[error("replay version {0} is not supported, expected {REPLAY_VERSION}")]
+    UnsupportedVersion(u32),
+}
+
+// one entry per run of identical ticks, as (input bitmask, tick count)
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ReplayInputs(Vec<(u8, u32)>);
+
+impl ReplayInputs {
+    pub fn push(&mut self, input: MovementInput) {
+        let bits = input.to_bits();
+
+        match self.0.last_mut() {
+            Some((last_bits, count)) if *last_bits == bits => *count += 1,
+            _ => self.0.push((bits, 1)),
+        }
+    }
+
+    pub fn get_tick_count(&self) -> usize {
+        self.0.iter().map(|(_, count)| *count as usize).sum()
+    }
+
+    pub fn to_ticks(&self) -> Vec<MovementInput> {
+        self.0
+            .iter()
+            .flat_map(|(bits, count)| {
+                std::iter::repeat_n(MovementInput::from_bits(*bits), *count as usize)
+            })
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ReplayHeader {
+    version: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+
+    pub level_seed: i64,
+    pub floor: i64,
+
+    pub player_properties: PlayerProperties,
+
+    // the rules the floor was played under, the run ends differently under any others
+    pub completion_threshold: f64,
+    pub section_weights: SectionWeights,
+
+    // simulation ticks per second the inputs were recorded at
+    pub tick_rate: i64,
+
+    pub final_ticks: i64,
+
+    pub inputs: ReplayInputs,
+}
+
+impl Replay {
+    pub fn new(
+        save_game: &SaveGame,
+        completion_rules: &CompletionRules,
+        final_ticks: i64,
+        inputs: ReplayInputs,
+    ) -> Replay {
+        Replay {
+            version: REPLAY_VERSION,
+
+            level_seed: save_game.level_seed,
+            floor: save_game.current_floor,
+
+            player_properties: save_game.player_properties.clone(),
+
+            completion_threshold: completion_rules.get_threshold(save_game.current_floor),
+            section_weights: completion_rules.section_weights.clone(),
+
+            tick_rate: SIMULATION_TICK_RATE,
+
+            final_ticks,
+
+            inputs,
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Replay, ReplayError> {
+        // checked first so a newer format fails with a useful message
+        let header = serde_json::from_str::<ReplayHeader>(text)?;
+
+        if header.version != REPLAY_VERSION {
+            return Err(ReplayError::UnsupportedVersion(header.version));
+        }
+
+        Ok(serde_json::from_str::<Replay>(text)?)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    // the save game the replayed floor is generated from, nothing in it is ever saved
+    pub fn get_save_game(&self) -> SaveGame {
+        let mut save_game = SaveGame::new(self.level_seed);
+
+        save_game.current_floor = self.floor;
+        save_game.player_properties = self.player_properties.clone();
+
+        save_game
+    }
+
+    // only the bonus is taken from the current rules, it never changes how the run plays out
+    pub fn get_completion_rules(&self, current_rules: &CompletionRules) -> CompletionRules {
+        CompletionRules {
+            section_weights: self.section_weights.clone(),
+
+            thresholds: vec![CompletionThreshold {
+                from_floor: 0,
+                threshold: self.completion_threshold,
+            }],
+
+            full_reveal_bonus: current_rules.full_reveal_bonus,
+        }
+    }
+
+    pub fn get_file_name(&self, timestamp: i64) -> String {
+        format!(
+            "seed_{}_floor_{}_{timestamp}.replay",
+            self.level_seed, self.floor
+        )
+    }
+}
@@ -0,0 +1,110 @@
+use crate::types::floor_layout::ROOM_GRID_BASIS;
+
+// the names sections are revealed under, and the order every [bool; 7] of them is kept in
+pub const ROOM_SECTIONS: [&str; 7] = [
+    "horizontal_corridors",
+    "vertical_corridors",
+    "center",
+    "top_left",
+    "top_right",
+    "bottom_left",
+    "bottom_right",
+];
+
+const HORIZONTAL_CORRIDORS: usize = 0;
+const VERTICAL_CORRIDORS: usize = 1;
+const CENTER: usize = 2;
+const TOP_LEFT: usize = 3;
+const TOP_RIGHT: usize = 4;
+const BOTTOM_LEFT: usize = 5;
+const BOTTOM_RIGHT: usize = 6;
+
+// the sections of a room a viewer uncovers, shared by the fog and the par time solver so
+// both agree on what counts as seen
+pub fn get_sections_seen_from(room_position: (f64, f64), viewer_position: (f64, f64)) -> [bool; 7] {
+    let mut seen = [false; 7];
+
+    let (room_x, room_y) = room_position;
+    let (viewer_x, viewer_y) = viewer_position;
+
+    let viewer_distance = (room_x - viewer_x).hypot(room_y - viewer_y);
+
+    let grid_basis = ROOM_GRID_BASIS as f64;
+    let half_grid_basis = grid_basis / 2.0;
+
+    if viewer_y < room_y + 1.5 && viewer_y > room_y - 1.5 {
+        seen[HORIZONTAL_CORRIDORS] = true;
+        seen[CENTER] = true;
+    }
+
+    if viewer_x < room_x + 1.5 && viewer_x > room_x - 1.5 {
+        seen[VERTICAL_CORRIDORS] = true;
+        seen[CENTER] = true;
+    }
+
+    let is_left = viewer_x < room_x - half_grid_basis
+        && viewer_y > room_y - half_grid_basis
+        && viewer_y < room_y + half_grid_basis;
+    let is_in_column = viewer_x > room_x - half_grid_basis && viewer_x < room_x + half_grid_basis;
+    let is_right = viewer_x > room_x + half_grid_basis
+        && viewer_y > room_y - half_grid_basis
+        && viewer_y < room_y + half_grid_basis;
+
+    let is_below = viewer_y < room_y - half_grid_basis;
+    let is_inside = viewer_y > room_y - half_grid_basis && viewer_y < room_y + half_grid_basis;
+    let is_above = viewer_y > room_y + half_grid_basis;
+
+    if is_left {
+        seen[TOP_RIGHT] = true;
+        seen[BOTTOM_RIGHT] = true;
+    } else if is_in_column {
+        if is_below {
+            seen[TOP_LEFT] = true;
+            seen[TOP_RIGHT] = true;
+        } else if is_inside {
+            seen[TOP_LEFT] = true;
+            seen[TOP_RIGHT] = true;
+            seen[BOTTOM_LEFT] = true;
+            seen[BOTTOM_RIGHT] = true;
+        } else if is_above {
+            seen[BOTTOM_LEFT] = true;
+            seen[BOTTOM_RIGHT] = true;
+        }
+    } else if is_right {
+        seen[TOP_LEFT] = true;
+        seen[BOTTOM_LEFT] = true;
+    }
+
+    // the corridors across the line of sight only show up close by
+    if viewer_distance < grid_basis {
+        if is_left || is_right {
+            seen[VERTICAL_CORRIDORS] = true;
+        } else if is_in_column {
+            if is_below || is_above {
+                seen[HORIZONTAL_CORRIDORS] = true;
+            } else if is_inside {
+                seen[VERTICAL_CORRIDORS] = true;
+                seen[HORIZONTAL_CORRIDORS] = true;
+            }
+        }
+    }
+
+    seen
+}
+
+pub fn merge_sections(seen_sections: [bool; 7], other: [bool; 7]) -> [bool; 7] {
+    std::array::from_fn(|i| seen_sections[i] || other[i])
+}
+
+// names of the sections seen now that were not seen before
+pub fn get_newly_seen_sections(
+    last_seen_sections: [bool; 7],
+    seen_sections: [bool; 7],
+) -> Vec<&'static str> {
+    ROOM_SECTIONS
+        .iter()
+        .zip(seen_sections.iter().zip(last_seen_sections.iter()))
+        .filter(|(_, (seen, last_seen))| **seen && !**last_seen)
+        .map(|(section, _)| *section)
+        .collect()
+}
@@ -1,4 +1,56 @@
-#[derive(Clone)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum RoomType {
     Normal,
+    Treasure(i64),
+    Hazard(HazardType),
+    Teleporter((i64, i64)),
+}
+
+impl RoomType {
+    pub fn get_stop_penalty(&self) -> f64 {
+        match self {
+            RoomType::Hazard(hazard) => hazard.get_stop_penalty(),
+            _ => 0.0,
+        }
+    }
+
+    pub fn forces_movement(&self) -> bool {
+        match self {
+            RoomType::Hazard(hazard) => hazard.forces_movement(),
+            _ => false,
+        }
+    }
+
+    pub fn get_teleport_destination(&self) -> Option<(i64, i64)> {
+        match self {
+            RoomType::Teleporter(partner) => Some(*partner),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HazardType {
+    Spikes,
+    Ice,
+}
+
+impl HazardType {
+    pub fn get_stop_penalty(&self) -> f64 {
+        match self {
+            HazardType::Spikes => 1.0,
+            HazardType::Ice => 0.0,
+        }
+    }
+
+    pub fn forces_movement(&self) -> bool {
+        match self {
+            HazardType::Spikes => false,
+            HazardType::Ice => true,
+        }
+    }
 }
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::types::player_properties::PlayerProperties;
+use crate::types::seed::SeedTree;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SaveGame {
@@ -14,6 +15,10 @@ pub struct SaveGame {
     pub money: i64,
 
     pub player_properties: PlayerProperties,
+
+    // the map view is folded away for the minimap, older saves have it open
+    #[serde(default)]
+    pub show_minimap: bool,
 }
 
 impl SaveGame {
@@ -29,27 +34,19 @@ impl SaveGame {
             money: 0,
 
             player_properties: PlayerProperties::default(),
+
+            show_minimap: false,
         }
     }
 
-    pub fn get_rng_seed(&self) -> [u8; 32] {
-        self.level_seed
-            .to_le_bytes()
-            .iter()
-            .chain(&self.current_floor.to_le_bytes())
-            .chain(
-                &(if self.in_shop {
-                    (self.mod_shop_page as i64) + 1i64
-                } else {
-                    0i64
-                })
-                .to_le_bytes(),
-            )
-            .chain(&self.money.to_le_bytes())
-            .copied()
-            .collect::<Vec<u8>>()
-            .as_slice()
-            .try_into()
-            .unwrap()
+    pub fn get_floor_seed(&self) -> SeedTree {
+        SeedTree::new(self.level_seed).floor(self.current_floor)
+    }
+
+    // money and shop state never change the floor, only its seed and the par time
+    pub fn generates_same_floor(&self, other: &SaveGame) -> bool {
+        self.level_seed == other.level_seed
+            && self.current_floor == other.current_floor
+            && self.player_properties == other.player_properties
     }
 }
@@ -0,0 +1,76 @@
+use rand::SeedableRng;
+use rand::rngs::SmallRng;
+
+#[derive(Clone, Copy, Debug)]
+pub enum SeedStream {
+    FloorLayout,
+    ShopOffers,
+    RoomContent,
+}
+
+impl SeedStream {
+    fn get_name(&self) -> &'static str {
+        match self {
+            SeedStream::FloorLayout => "floor_layout",
+            SeedStream::ShopOffers => "shop_offers",
+            SeedStream::RoomContent => "room_content",
+        }
+    }
+}
+
+// each node is derived only from its parent and a key, so streams never
+// influence each other no matter how much of one is consumed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SeedTree(u64);
+
+impl SeedTree {
+    pub fn new(run_seed: i64) -> SeedTree {
+        SeedTree(Self::mix(run_seed as u64))
+    }
+
+    pub fn child(self, key: u64) -> SeedTree {
+        SeedTree(Self::mix(self.0.rotate_left(17) ^ Self::mix(key)))
+    }
+
+    pub fn floor(self, floor: i64) -> SeedTree {
+        self.child(floor as u64)
+    }
+
+    pub fn stream(self, stream: SeedStream) -> SeedTree {
+        // fnv-1a, stable across builds unlike the std hasher
+        let key = stream
+            .get_name()
+            .bytes()
+            .fold(0xcbf29ce484222325u64, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+            });
+
+        self.child(key)
+    }
+
+    pub fn position(self, (x, y): (i64, i64)) -> SeedTree {
+        self.child(x as u64).child(y as u64)
+    }
+
+    pub fn to_bytes(self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+
+        for (i, chunk) in bytes.chunks_exact_mut(8).enumerate() {
+            chunk.copy_from_slice(&Self::mix(self.0.wrapping_add(i as u64)).to_le_bytes());
+        }
+
+        bytes
+    }
+
+    pub fn rng(self) -> SmallRng {
+        SmallRng::from_seed(self.to_bytes())
+    }
+
+    // splitmix64 finalizer
+    fn mix(value: u64) -> u64 {
+        let mut z = value.wrapping_add(0x9e3779b97f4a7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+}
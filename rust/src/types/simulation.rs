@@ -0,0 +1,46 @@
+// the game is always simulated at this rate, however often godot calls physics_process
+pub const SIMULATION_TICK_RATE: i64 = 480;
+pub const SIMULATION_TICK_DELTA: f64 = 1.0 / SIMULATION_TICK_RATE as f64;
+
+// a frame hitch never runs more than this many ticks at once, the rest is dropped
+const MAX_TICKS_PER_STEP: i64 = 64;
+
+// frame deltas are summed in floating point, this keeps a delta of exactly one tick from
+// being a hair short after rounding
+const TICK_EPSILON: f64 = 1e-9;
+
+pub fn ticks_to_seconds(ticks: i64) -> f64 {
+    ticks as f64 * SIMULATION_TICK_DELTA
+}
+
+pub fn seconds_to_ticks(seconds: f64) -> i64 {
+    (seconds * SIMULATION_TICK_RATE as f64).round() as i64
+}
+
+// turns whatever delta the engine hands over into a whole number of fixed ticks
+#[derive(Clone, Debug, Default)]
+pub struct TickAccumulator {
+    accumulated: f64,
+}
+
+impl TickAccumulator {
+    pub fn reset(&mut self) {
+        self.accumulated = 0.0;
+    }
+
+    pub fn advance(&mut self, delta: f64) -> i64 {
+        self.accumulated += delta;
+
+        let ticks = ((self.accumulated + TICK_EPSILON) / SIMULATION_TICK_DELTA).floor() as i64;
+
+        if ticks > MAX_TICKS_PER_STEP {
+            self.accumulated = 0.0;
+
+            return MAX_TICKS_PER_STEP;
+        }
+
+        self.accumulated = (self.accumulated - ticks as f64 * SIMULATION_TICK_DELTA).max(0.0);
+
+        ticks
+    }
+}
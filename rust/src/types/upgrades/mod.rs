@@ -1,5 +1,6 @@
 use rand::Rng;
 
+use crate::types::player_properties::{ExtraEyeProperties, MomentumRedirectorProperties};
 use crate::types::save_game::SaveGame;
 
 struct UpgradeTable;
@@ -45,6 +46,40 @@ impl UpgradeTable {
 
         save
     }
+
+    fn add_move_buffer(mut save: SaveGame, time: f64) -> SaveGame {
+        save.player_properties.has_move_buffer = true;
+        save.player_properties.move_buffer_window += time;
+
+        save
+    }
+
+    // buying it again only ever shortens the cooldown
+    fn add_momentum_redirector(mut save: SaveGame, cooldown: f64) -> SaveGame {
+        let cooldown = match save.player_properties.momentum_redirector {
+            Some(ref redirector) => redirector.cooldown.min(cooldown),
+            None => cooldown,
+        };
+
+        save.player_properties.momentum_redirector =
+            Some(MomentumRedirectorProperties { cooldown });
+
+        save
+    }
+
+    fn add_extra_eyes(mut save: SaveGame, count: i64) -> SaveGame {
+        match save.player_properties.extra_eye {
+            Some(ref mut extra_eye) => extra_eye.count += count,
+            None => {
+                save.player_properties.extra_eye = Some(ExtraEyeProperties {
+                    count,
+                    ..ExtraEyeProperties::default()
+                })
+            }
+        }
+
+        save
+    }
 }
 
 // use proc macro to generate
@@ -62,6 +97,12 @@ pub enum UpgradeType {
     AddViewDistance(f64),
 
     DivideMass(f64),
+
+    AddMoveBuffer(f64),
+
+    AddMomentumRedirector(f64),
+
+    AddExtraEyes(i64),
 }
 
 impl UpgradeType {
@@ -84,6 +125,14 @@ impl UpgradeType {
             }
 
             UpgradeType::DivideMass(divisor) => UpgradeTable::divide_mass(save, divisor),
+
+            UpgradeType::AddMoveBuffer(time) => UpgradeTable::add_move_buffer(save, time),
+
+            UpgradeType::AddMomentumRedirector(cooldown) => {
+                UpgradeTable::add_momentum_redirector(save, cooldown)
+            }
+
+            UpgradeType::AddExtraEyes(count) => UpgradeTable::add_extra_eyes(save, count),
         }
     }
 
@@ -100,6 +149,12 @@ impl UpgradeType {
             UpgradeType::AddViewDistance(_distance) => "Enhanced Eyes",
 
             UpgradeType::DivideMass(_divisor) => "Gym Membership",
+
+            UpgradeType::AddMoveBuffer(_time) => "Quick Reflexes",
+
+            UpgradeType::AddMomentumRedirector(_cooldown) => "Momentum Redirector",
+
+            UpgradeType::AddExtraEyes(_count) => "Extra Eye",
         }
         .to_owned()
     }
@@ -119,6 +174,14 @@ impl UpgradeType {
             UpgradeType::AddViewDistance(distance) => format!("view distance +{distance:.1}m"),
 
             UpgradeType::DivideMass(divisor) => format!("mass /{divisor:.1}"),
+
+            UpgradeType::AddMoveBuffer(time) => format!("turn buffer +{time:.2}s"),
+
+            UpgradeType::AddMomentumRedirector(cooldown) => {
+                format!("turn without stopping, {cooldown:.1}s cooldown")
+            }
+
+            UpgradeType::AddExtraEyes(count) => format!("scouting eyes +{count} per floor"),
         }
     }
 
@@ -135,6 +198,12 @@ impl UpgradeType {
             UpgradeType::AddViewDistance(distance) => 4 + (distance.floor() as i64),
 
             UpgradeType::DivideMass(divisor) => 6 + (divisor.floor() as i64),
+
+            UpgradeType::AddMoveBuffer(time) => 3 + ((time * 10.0).floor() as i64),
+
+            UpgradeType::AddMomentumRedirector(cooldown) => 20 - (cooldown.floor() as i64),
+
+            UpgradeType::AddExtraEyes(count) => 4 + (count * 3),
         }
     }
 
@@ -151,11 +220,17 @@ impl UpgradeType {
             UpgradeType::AddViewDistance(distance) => 0.1 / distance,
 
             UpgradeType::DivideMass(divisor) => 0.1 / divisor,
+
+            UpgradeType::AddMoveBuffer(time) => 0.01 / time,
+
+            UpgradeType::AddMomentumRedirector(cooldown) => 0.005 * cooldown,
+
+            UpgradeType::AddExtraEyes(count) => 0.1 / *count as f64,
         }
     }
 
     pub fn generate_random(random: &mut impl Rng) -> Self {
-        let upgrade_number = random.random_range(0..=6);
+        let upgrade_number = random.random_range(0..=9);
 
         match upgrade_number {
             0 => UpgradeType::AddWarmup(random.random_range(1.0..=5.0)),
@@ -170,6 +245,12 @@ impl UpgradeType {
 
             6 => UpgradeType::DivideMass(random.random_range(1.2..=2.5)),
 
+            7 => UpgradeType::AddMoveBuffer(random.random_range(0.05..=0.2)),
+
+            8 => UpgradeType::AddMomentumRedirector(random.random_range(6.0..=12.0)),
+
+            9 => UpgradeType::AddExtraEyes(random.random_range(1..=2)),
+
             x => panic!("impossible upgrade {}", x),
         }
     }